@@ -0,0 +1,67 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Instruction-count benchmarks for the hot operations, measured under
+//! Callgrind so that results are deterministic and comparable across runs.
+//!
+//! Needs valgrind and a matching runner, installed with
+//! `cargo install iai-callgrind-runner --version 0.16.1`. Run with
+//! `cargo bench --features iai --bench callgrind`.
+
+extern crate iai_callgrind;
+extern crate smallset;
+
+use iai_callgrind::{library_benchmark, library_benchmark_group, main};
+use smallset::SmallSet;
+use std::hint::black_box;
+
+type Set = SmallSet<[u32; 8]>;
+
+fn filled(n: u32) -> Set {
+    (0..n).collect()
+}
+
+#[library_benchmark]
+#[bench::empty(filled(0))]
+#[bench::half(filled(4))]
+#[bench::full_minus_one(filled(7))]
+fn insert_new(mut s: Set) -> Set {
+    s.insert(black_box(100));
+    s
+}
+
+#[library_benchmark]
+#[bench::half(filled(4))]
+#[bench::spilled(filled(16))]
+fn insert_present(mut s: Set) -> Set {
+    s.insert(black_box(0));
+    s
+}
+
+#[library_benchmark]
+#[bench::first(filled(8), 0)]
+#[bench::last(filled(8), 7)]
+#[bench::miss(filled(8), 100)]
+#[bench::spilled_miss(filled(16), 100)]
+fn contains(s: Set, elem: u32) -> bool {
+    black_box(&s).contains(black_box(&elem))
+}
+
+/// Inserting the ninth element moves the set from inline storage onto the
+/// heap.
+#[library_benchmark]
+#[bench::promote(filled(8))]
+fn promotion(mut s: Set) -> Set {
+    s.insert(black_box(8));
+    s
+}
+
+library_benchmark_group!(
+    name = smallset_ops;
+    benchmarks = insert_new, insert_present, contains, promotion
+);
+
+main!(library_benchmark_groups = smallset_ops);
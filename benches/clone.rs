@@ -0,0 +1,67 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Times `clone`, which the `nightly` feature speeds up for `Copy` elements,
+//! for inline and spilled sets. `union_into` copies elements through
+//! `extend`, which the feature does not change, and `String` elements are not
+//! `Copy`; both are included as controls.
+//!
+//! Compare `cargo bench --bench clone` with
+//! `cargo +nightly bench --features nightly --bench clone`. Set
+//! `SMALLSET_BENCH_MS` to change the time spent on each measurement (default
+//! 200ms).
+
+extern crate smallset;
+
+use smallset::SmallSet;
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+type Set<T> = SmallSet<[T; 8]>;
+
+/// Returns the average time per call of `f`, in nanoseconds.
+fn measure<F: FnMut()>(budget: Duration, mut f: F) -> f64 {
+    let start = Instant::now();
+    let mut calls = 0u64;
+    while start.elapsed() < budget {
+        for _ in 0..64 {
+            f();
+        }
+        calls += 64;
+    }
+    start.elapsed().as_nanos() as f64 / calls as f64
+}
+
+fn report<T: PartialEq + Eq + Clone>(name: &str, make: fn(usize) -> T, budget: Duration) {
+    for &n in &[8, 64, 512] {
+        let set: Set<T> = (0..n).map(make).collect();
+        let empty: Set<T> = SmallSet::new();
+        let mut out: Set<T> = SmallSet::with_capacity(n);
+        let clone = measure(budget, || {
+            black_box(black_box(&set).clone());
+        });
+        let union = measure(budget, || {
+            black_box(&set).union_into(black_box(&empty), &mut out);
+            black_box(&out);
+        });
+        println!(
+            "{:>8} n={:<4} clone {:>9.1} ns   union_into {:>9.1} ns",
+            name, n, clone, union
+        );
+    }
+}
+
+fn main() {
+    let ms = env::var("SMALLSET_BENCH_MS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(200);
+    let budget = Duration::from_millis(ms);
+    report::<u32>("u32", |i| i as u32, budget);
+    report::<u64>("u64", |i| i as u64, budget);
+    report::<String>("String", |i| format!("element-{}", i), budget);
+}
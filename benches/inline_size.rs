@@ -0,0 +1,151 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Sweeps inline capacities and element types over a mixed workload and
+//! reports the time per operation for each combination, along with the
+//! fastest inline capacity for each set size.
+//!
+//! Run with `cargo bench --bench inline_size`. Set `SMALLSET_BENCH_MS` to
+//! change the time spent on each measurement (default 20ms).
+
+extern crate smallset;
+extern crate smallvec;
+
+use smallset::SmallSet;
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const SET_SIZES: &[usize] = &[1, 2, 4, 8, 16, 32, 64];
+
+/// Element types exercised by the sweep. Elements are produced from a small
+/// integer so that hits and misses are cheap to generate.
+trait Elem: PartialEq + Eq + Clone {
+    const NAME: &'static str;
+    fn make(i: usize) -> Self;
+}
+
+impl Elem for u32 {
+    const NAME: &'static str = "u32";
+    fn make(i: usize) -> u32 {
+        i as u32
+    }
+}
+
+impl Elem for u64 {
+    const NAME: &'static str = "u64";
+    fn make(i: usize) -> u64 {
+        (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+    }
+}
+
+impl Elem for String {
+    const NAME: &'static str = "String";
+    fn make(i: usize) -> String {
+        format!("element-{}", i)
+    }
+}
+
+/// One round of the workload on a set that grows to `n` elements: fill it,
+/// probe it with an equal mix of hits and misses, then remove and re-insert
+/// half of the elements. Returns the number of set operations performed.
+fn workload<A>(elems: &[A::Item], n: usize) -> usize
+where
+    A: smallvec::Array,
+    A::Item: Elem,
+{
+    let mut set: SmallSet<A> = SmallSet::new();
+    for e in &elems[..n] {
+        set.insert(e.clone());
+    }
+    let mut hits = 0;
+    for i in 0..(4 * n) {
+        if set.contains(&elems[i % (2 * n)]) {
+            hits += 1;
+        }
+    }
+    black_box(hits);
+    for e in &elems[..n / 2] {
+        set.remove(e);
+    }
+    for e in &elems[..n / 2] {
+        set.insert(e.clone());
+    }
+    black_box(&set);
+    n + 4 * n + 2 * (n / 2)
+}
+
+/// Returns the average time per set operation, in nanoseconds.
+fn measure<A>(n: usize, budget: Duration) -> f64
+where
+    A: smallvec::Array,
+    A::Item: Elem,
+{
+    let elems: Vec<A::Item> = (0..(2 * n)).map(A::Item::make).collect();
+    let start = Instant::now();
+    let mut ops = 0;
+    while start.elapsed() < budget {
+        ops += workload::<A>(black_box(&elems), n);
+    }
+    start.elapsed().as_nanos() as f64 / ops as f64
+}
+
+/// Times every set size for one inline capacity.
+fn sweep<A>(budget: Duration) -> (usize, Vec<f64>)
+where
+    A: smallvec::Array,
+    A::Item: Elem,
+{
+    let times = SET_SIZES.iter().map(|&n| measure::<A>(n, budget)).collect();
+    (A::size(), times)
+}
+
+fn report<T: Elem>(budget: Duration) {
+    let rows = vec![
+        sweep::<[T; 1]>(budget),
+        sweep::<[T; 2]>(budget),
+        sweep::<[T; 4]>(budget),
+        sweep::<[T; 8]>(budget),
+        sweep::<[T; 16]>(budget),
+        sweep::<[T; 32]>(budget),
+        sweep::<[T; 64]>(budget),
+    ];
+
+    println!("element type {} (ns/op)", T::NAME);
+    print!("{:>8}", "inline");
+    for n in SET_SIZES {
+        print!("{:>9}", format!("n={}", n));
+    }
+    println!();
+    for &(cap, ref times) in &rows {
+        print!("{:>8}", cap);
+        for t in times {
+            print!("{:>9.2}", t);
+        }
+        println!();
+    }
+    print!("{:>8}", "best");
+    for i in 0..SET_SIZES.len() {
+        let &(cap, _) = rows
+            .iter()
+            .min_by(|a, b| a.1[i].partial_cmp(&b.1[i]).unwrap())
+            .unwrap();
+        print!("{:>9}", cap);
+    }
+    println!();
+    println!();
+}
+
+fn main() {
+    let ms = env::var("SMALLSET_BENCH_MS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(20);
+    let budget = Duration::from_millis(ms);
+    report::<u32>(budget);
+    report::<u64>(budget);
+    report::<String>(budget);
+}
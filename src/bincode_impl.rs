@@ -0,0 +1,116 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Native `bincode` 2 support, enabled with the `bincode` feature.
+//!
+//! A set is encoded like a `Vec`: a length prefix followed by the elements in
+//! iteration order. Decoding claims the whole container against the
+//! configured size limit before reading any element, so a corrupt or hostile
+//! length prefix fails with `LimitExceeded` rather than driving a large
+//! allocation. Duplicate elements in the input are dropped.
+
+use bincode::de::{BorrowDecode, BorrowDecoder, Decode, Decoder};
+use bincode::enc::{Encode, Encoder};
+use bincode::error::{DecodeError, EncodeError};
+use smallvec::Array;
+use std::convert::TryFrom;
+use std::mem;
+
+use SmallSet;
+
+/// Decodes a length prefix, encoded (as bincode does for `Vec`) as a `u64`.
+fn decode_len<Context, D: Decoder<Context = Context>>(
+    decoder: &mut D,
+) -> Result<usize, DecodeError> {
+    let len = u64::decode(decoder)?;
+    usize::try_from(len).map_err(|_| DecodeError::OutsideUsizeRange(len))
+}
+
+impl<A: Array> Encode for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Encode,
+{
+    fn encode<E: Encoder>(&self, encoder: &mut E) -> Result<(), EncodeError> {
+        (self.len() as u64).encode(encoder)?;
+        for elem in self.iter() {
+            elem.encode(encoder)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Context, A: Array> Decode<Context> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Decode<Context>,
+{
+    fn decode<D: Decoder<Context = Context>>(decoder: &mut D) -> Result<Self, DecodeError> {
+        let len = decode_len(decoder)?;
+        decoder.claim_container_read::<A::Item>(len)?;
+        let mut set = SmallSet::new();
+        for _ in 0..len {
+            // The container claim above already accounted for this element.
+            decoder.unclaim_bytes_read(mem::size_of::<A::Item>());
+            set.insert(A::Item::decode(decoder)?);
+        }
+        Ok(set)
+    }
+}
+
+impl<'de, Context, A: Array> BorrowDecode<'de, Context> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + BorrowDecode<'de, Context>,
+{
+    fn borrow_decode<D: BorrowDecoder<'de, Context = Context>>(
+        decoder: &mut D,
+    ) -> Result<Self, DecodeError> {
+        let len = decode_len(decoder)?;
+        decoder.claim_container_read::<A::Item>(len)?;
+        let mut set = SmallSet::new();
+        for _ in 0..len {
+            decoder.unclaim_bytes_read(mem::size_of::<A::Item>());
+            set.insert(A::Item::borrow_decode(decoder)?);
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bincode::config;
+
+    #[test]
+    fn test_roundtrip() {
+        let s: SmallSet<[u32; 2]> = vec![3, 1, 2].into_iter().collect();
+        let bytes = bincode::encode_to_vec(&s, config::standard()).unwrap();
+        assert!(bytes == vec![3, 3, 1, 2]);
+        let (d, n): (SmallSet<[u32; 2]>, usize) =
+            bincode::decode_from_slice(&bytes, config::standard()).unwrap();
+        assert!(n == 4);
+        assert!(d.iter().copied().collect::<Vec<u32>>() == vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let s: SmallSet<[&str; 2]> = vec!["a", "b"].into_iter().collect();
+        let bytes = bincode::encode_to_vec(&s, config::standard()).unwrap();
+        let (d, _): (SmallSet<[&str; 2]>, usize) =
+            bincode::borrow_decode_from_slice(&bytes, config::standard()).unwrap();
+        assert!(d.iter().copied().collect::<Vec<&str>>() == vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_dedup_and_limit() {
+        let (d, _): (SmallSet<[u8; 2]>, usize) =
+            bincode::decode_from_slice(&[3, 7, 7, 8], config::standard()).unwrap();
+        assert!(d.iter().copied().collect::<Vec<u8>>() == vec![7, 8]);
+
+        let limited = config::standard().with_limit::<16>();
+        let r: Result<(SmallSet<[u64; 2]>, usize), _> =
+            bincode::decode_from_slice(&[100, 0, 0, 0], limited);
+        assert!(matches!(r, Err(DecodeError::LimitExceeded)));
+    }
+}
@@ -0,0 +1,342 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A fixed-size bitset for elements that map to small integers.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+/// An element type that maps one-to-one onto the bit positions `0..n` of a
+/// `SmallBitSet`. It is implemented for the unsigned integers and `bool`, and
+/// can be implemented for fieldless enums by casting the discriminant.
+///
+/// ```
+/// use smallset::BitElement;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// enum Color { Red, Green, Blue }
+///
+/// impl BitElement for Color {
+///     fn to_bit(&self) -> usize {
+///         *self as usize
+///     }
+///     fn from_bit(bit: usize) -> Color {
+///         [Color::Red, Color::Green, Color::Blue][bit]
+///     }
+/// }
+/// ```
+pub trait BitElement {
+    /// Returns the bit position of this element.
+    fn to_bit(&self) -> usize;
+    /// Returns the element at bit position `bit`, which was previously
+    /// returned by `to_bit`.
+    fn from_bit(bit: usize) -> Self;
+}
+
+macro_rules! bit_element_uint {
+    ($($t:ty),*) => {
+        $(
+            impl BitElement for $t {
+                #[inline]
+                fn to_bit(&self) -> usize {
+                    *self as usize
+                }
+
+                #[inline]
+                fn from_bit(bit: usize) -> $t {
+                    bit as $t
+                }
+            }
+        )*
+    };
+}
+
+bit_element_uint!(u8, u16, u32, usize);
+
+impl BitElement for bool {
+    #[inline]
+    fn to_bit(&self) -> usize {
+        *self as usize
+    }
+
+    #[inline]
+    fn from_bit(bit: usize) -> bool {
+        bit != 0
+    }
+}
+
+/// A `SmallBitSet` stores membership of elements as bits in `W` inline
+/// 64-bit words, so it can hold elements whose bit positions are below
+/// `64 * W`. Insertion, removal and lookup are O(1), and the set operations
+/// work a word at a time. For `u8` elements, `W = 4` covers every value; a
+/// fieldless enum with up to 64 variants needs `W = 1`.
+///
+/// Inserting an element whose bit position does not fit panics. Iteration is
+/// in ascending bit order, not insertion order.
+///
+/// ```
+/// use smallset::SmallBitSet;
+///
+/// let mut s: SmallBitSet<u8, 4> = SmallBitSet::new();
+/// s.insert(200);
+/// s.insert(3);
+/// assert!(s.contains(&200) && !s.contains(&4));
+/// assert!(s.iter().collect::<Vec<u8>>() == vec![3, 200]);
+/// ```
+pub struct SmallBitSet<T, const W: usize> {
+    words: [u64; W],
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: BitElement, const W: usize> SmallBitSet<T, W> {
+    /// Creates a new, empty `SmallBitSet`.
+    #[inline]
+    pub const fn new() -> SmallBitSet<T, W> {
+        SmallBitSet {
+            words: [0; W],
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the word index and mask for `elem`, panicking if it does not
+    /// fit.
+    #[inline]
+    fn locate(elem: &T) -> (usize, u64) {
+        let bit = elem.to_bit();
+        assert!(
+            bit < 64 * W,
+            "bit position {} out of range for SmallBitSet",
+            bit
+        );
+        (bit / 64, 1 << (bit % 64))
+    }
+
+    /// Inserts `elem`. Returns `true` if it was not yet present. Panics if
+    /// `elem`'s bit position is `64 * W` or more.
+    #[inline]
+    pub fn insert(&mut self, elem: T) -> bool {
+        let (w, mask) = Self::locate(&elem);
+        let new = self.words[w] & mask == 0;
+        self.words[w] |= mask;
+        new
+    }
+
+    /// Removes `elem`. Returns `true` if it was present.
+    #[inline]
+    pub fn remove(&mut self, elem: &T) -> bool {
+        if elem.to_bit() >= 64 * W {
+            return false;
+        }
+        let (w, mask) = Self::locate(elem);
+        let present = self.words[w] & mask != 0;
+        self.words[w] &= !mask;
+        present
+    }
+
+    /// Tests whether `elem` is present.
+    #[inline]
+    pub fn contains(&self, elem: &T) -> bool {
+        if elem.to_bit() >= 64 * W {
+            return false;
+        }
+        let (w, mask) = Self::locate(elem);
+        self.words[w] & mask != 0
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns `true` if the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Clears the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.words = [0; W];
+    }
+
+    /// Returns an iterator over the elements, in ascending bit order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, W> {
+        Iter {
+            set: self,
+            word: 0,
+            bits: if W > 0 { self.words[0] } else { 0 },
+        }
+    }
+
+    /// Adds every element of `other`. Returns `true` if any element was added.
+    pub fn union_with(&mut self, other: &SmallBitSet<T, W>) -> bool {
+        self.update(other, |a, b| a | b)
+    }
+
+    /// Removes every element not in `other`. Returns `true` if any element was
+    /// removed.
+    pub fn intersection_with(&mut self, other: &SmallBitSet<T, W>) -> bool {
+        self.update(other, |a, b| a & b)
+    }
+
+    /// Removes every element in `other`. Returns `true` if any element was
+    /// removed.
+    pub fn difference_with(&mut self, other: &SmallBitSet<T, W>) -> bool {
+        self.update(other, |a, b| a & !b)
+    }
+
+    /// Keeps the elements in exactly one of `self` and `other`. Returns `true`
+    /// if the set changed.
+    pub fn symmetric_difference_with(&mut self, other: &SmallBitSet<T, W>) -> bool {
+        self.update(other, |a, b| a ^ b)
+    }
+
+    /// Returns `true` if every element of `self` is in `other`.
+    pub fn is_subset(&self, other: &SmallBitSet<T, W>) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(a, b)| a & !b == 0)
+    }
+
+    /// Returns `true` if `self` and `other` have no elements in common.
+    pub fn is_disjoint(&self, other: &SmallBitSet<T, W>) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    #[inline]
+    fn update<F: Fn(u64, u64) -> u64>(&mut self, other: &SmallBitSet<T, W>, f: F) -> bool {
+        let mut changed = false;
+        for (a, &b) in self.words.iter_mut().zip(&other.words) {
+            let new = f(*a, b);
+            changed |= new != *a;
+            *a = new;
+        }
+        changed
+    }
+}
+
+/// An iterator over the elements of a `SmallBitSet`, in ascending bit order.
+pub struct Iter<'a, T: 'a, const W: usize> {
+    set: &'a SmallBitSet<T, W>,
+    word: usize,
+    bits: u64,
+}
+
+impl<'a, T: BitElement, const W: usize> Iterator for Iter<'a, T, W> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.bits == 0 {
+            self.word += 1;
+            if self.word >= W {
+                return None;
+            }
+            self.bits = self.set.words[self.word];
+        }
+        let bit = self.bits.trailing_zeros() as usize;
+        self.bits &= self.bits - 1;
+        Some(T::from_bit(self.word * 64 + bit))
+    }
+}
+
+impl<T: BitElement, const W: usize> Default for SmallBitSet<T, W> {
+    #[inline]
+    fn default() -> SmallBitSet<T, W> {
+        SmallBitSet::new()
+    }
+}
+
+impl<T, const W: usize> Clone for SmallBitSet<T, W> {
+    #[inline]
+    fn clone(&self) -> SmallBitSet<T, W> {
+        *self
+    }
+}
+
+impl<T, const W: usize> Copy for SmallBitSet<T, W> {}
+
+impl<T, const W: usize> PartialEq for SmallBitSet<T, W> {
+    #[inline]
+    fn eq(&self, other: &SmallBitSet<T, W>) -> bool {
+        self.words == other.words
+    }
+}
+
+impl<T, const W: usize> Eq for SmallBitSet<T, W> {}
+
+impl<T: BitElement + fmt::Debug, const W: usize> fmt::Debug for SmallBitSet<T, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: BitElement, const W: usize> FromIterator<T> for SmallBitSet<T, W> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut set = SmallBitSet::new();
+        for elem in iter {
+            set.insert(elem);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_insert_remove() {
+        let mut s: SmallBitSet<u8, 4> = SmallBitSet::new();
+        assert!(s.is_empty());
+        assert!(s.insert(0) && s.insert(64) && s.insert(255));
+        assert!(!s.insert(64));
+        assert!(s.len() == 3 && s.contains(&255) && !s.contains(&63));
+        assert!(s.iter().collect::<Vec<u8>>() == vec![0, 64, 255]);
+        assert!(s.remove(&64) && !s.remove(&64));
+        assert!(format!("{:?}", s) == "{0, 255}");
+        s.clear();
+        assert!(s.is_empty() && s.iter().next().is_none());
+
+        let b: SmallBitSet<bool, 1> = vec![true].into_iter().collect();
+        assert!(b.contains(&true) && !b.contains(&false));
+        let mut u: SmallBitSet<u32, 1> = SmallBitSet::new();
+        assert!(!u.contains(&1000) && !u.remove(&1000));
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_out_of_range() {
+        let mut s: SmallBitSet<u8, 1> = SmallBitSet::new();
+        s.insert(64);
+    }
+
+    #[test]
+    fn test_set_ops() {
+        let a: SmallBitSet<u8, 2> = vec![1, 2, 3, 100].into_iter().collect();
+        let b: SmallBitSet<u8, 2> = vec![3, 4, 100].into_iter().collect();
+        let mut c = a;
+        assert!(c.union_with(&b) && !c.union_with(&b));
+        assert!(c.iter().collect::<Vec<u8>>() == vec![1, 2, 3, 4, 100]);
+        assert!(a.is_subset(&c) && !c.is_subset(&a));
+        c.intersection_with(&a);
+        assert!(c == a);
+        assert!(c.difference_with(&b));
+        assert!(c.iter().collect::<Vec<u8>>() == vec![1, 2]);
+        assert!(c.is_disjoint(&b));
+        let mut d = a;
+        d.symmetric_difference_with(&b);
+        assert!(d.iter().collect::<Vec<u8>>() == vec![1, 2, 4]);
+    }
+}
@@ -0,0 +1,161 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A small Bloom filter.
+
+use smallvec::{smallvec, SmallVec};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// A `SmallBloom` is a Bloom filter whose bit array is stored inline in `N`
+/// 64-bit words, spilling to the heap when built with more bits than that. It
+/// answers "possibly present" or "definitely absent", and is meant for
+/// pre-filtering lookups that are expensive to perform, in the same niche as
+/// `SmallSet`.
+///
+/// Elements are hashed with a fixed-key SipHash, so a filter's contents are
+/// reproducible from run to run. Two filters can only be combined if they have
+/// the same number of bits and of hash functions.
+///
+/// ```
+/// use smallset::SmallBloom;
+///
+/// let mut f: SmallBloom<2> = SmallBloom::new();
+/// f.insert("apple");
+/// assert!(f.maybe_contains("apple"));
+/// ```
+#[derive(Clone)]
+pub struct SmallBloom<const N: usize> {
+    words: SmallVec<[u64; N]>,
+    hashes: u32,
+}
+
+/// The number of hash functions used by `SmallBloom::new`.
+const DEFAULT_HASHES: u32 = 3;
+
+impl<const N: usize> SmallBloom<N> {
+    /// Creates an empty filter using the `N` inline words (at least one) and
+    /// three hash functions.
+    pub fn new() -> SmallBloom<N> {
+        SmallBloom::with_bits(64 * N, DEFAULT_HASHES)
+    }
+
+    /// Creates an empty filter of at least `bits` bits, rounded up to a whole
+    /// number of words, using `hashes` hash functions. The filter spills to
+    /// the heap if it needs more than `N` words.
+    pub fn with_bits(bits: usize, hashes: u32) -> SmallBloom<N> {
+        let words = bits.div_ceil(64).max(1);
+        SmallBloom {
+            words: smallvec![0; words],
+            hashes: hashes.max(1),
+        }
+    }
+
+    /// Returns the bit positions for `elem`, derived from one 64-bit hash by
+    /// double hashing.
+    fn positions<T: Hash + ?Sized>(&self, elem: &T) -> impl Iterator<Item = usize> {
+        let mut hasher = DefaultHasher::new();
+        elem.hash(&mut hasher);
+        let h = hasher.finish();
+        let (h1, h2) = (h & 0xffff_ffff, (h >> 32) | 1);
+        let bits = (self.words.len() * 64) as u64;
+        (0..u64::from(self.hashes))
+            .map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bits) as usize)
+    }
+
+    /// Adds `elem` to the filter.
+    pub fn insert<T: Hash + ?Sized>(&mut self, elem: &T) {
+        for p in self.positions(elem) {
+            self.words[p / 64] |= 1 << (p % 64);
+        }
+    }
+
+    /// Returns `false` if `elem` was definitely never inserted, or `true` if
+    /// it may have been.
+    pub fn maybe_contains<T: Hash + ?Sized>(&self, elem: &T) -> bool {
+        self.positions(elem)
+            .all(|p| self.words[p / 64] & (1 << (p % 64)) != 0)
+    }
+
+    /// Adds every element of `other` to this filter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filters differ in size or number of hash functions.
+    pub fn union_with(&mut self, other: &SmallBloom<N>) {
+        assert!(
+            self.words.len() == other.words.len() && self.hashes == other.hashes,
+            "SmallBloom::union_with: filters have different parameters"
+        );
+        for (w, o) in self.words.iter_mut().zip(other.words.iter()) {
+            *w |= *o;
+        }
+    }
+
+    /// Removes all elements from the filter.
+    pub fn clear(&mut self) {
+        for w in self.words.iter_mut() {
+            *w = 0;
+        }
+    }
+
+    /// Returns `true` if nothing has been inserted since the filter was
+    /// created or cleared.
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Returns the number of bits in the filter.
+    pub fn num_bits(&self) -> usize {
+        self.words.len() * 64
+    }
+}
+
+impl<const N: usize> Default for SmallBloom<N> {
+    fn default() -> SmallBloom<N> {
+        SmallBloom::new()
+    }
+}
+
+impl<const N: usize> fmt::Debug for SmallBloom<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let set: u32 = self.words.iter().map(|w| w.count_ones()).sum();
+        write!(f, "SmallBloom({}/{} bits set)", set, self.num_bits())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives() {
+        let mut f: SmallBloom<4> = SmallBloom::new();
+        assert!(f.is_empty() && f.num_bits() == 256);
+        for i in 0..40u32 {
+            f.insert(&i);
+        }
+        assert!((0..40u32).all(|i| f.maybe_contains(&i)));
+        let false_positives = (1000..2000u32).filter(|i| f.maybe_contains(i)).count();
+        assert!(false_positives < 200);
+        f.clear();
+        assert!(f.is_empty() && !f.maybe_contains(&1u32));
+    }
+
+    #[test]
+    fn test_union_and_spill() {
+        let mut a: SmallBloom<1> = SmallBloom::with_bits(1000, 4);
+        assert!(a.words.spilled() && a.num_bits() == 1024);
+        let mut b = SmallBloom::with_bits(1000, 4);
+        a.insert("x");
+        b.insert("y");
+        a.union_with(&b);
+        assert!(a.maybe_contains("x") && a.maybe_contains("y"));
+        let z: SmallBloom<0> = SmallBloom::new();
+        assert!(z.num_bits() == 64 && !z.maybe_contains("x"));
+    }
+}
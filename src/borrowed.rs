@@ -0,0 +1,194 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A small set whose storage is a buffer supplied by the caller.
+
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ptr;
+use std::slice::{self, Iter};
+
+use errors::CapacityError;
+
+/// A `SmallSetRef` is a set stored in a caller-provided buffer of
+/// uninitialized slots, such as stack scratch space or a DMA region. It never
+/// allocates: inserting into a full buffer fails with a `CapacityError` that
+/// hands the element back.
+///
+/// Like `SmallSet`, elements are kept in insertion order and operations
+/// perform a linear scan. Elements still in the set are dropped when the
+/// `SmallSetRef` is dropped; the buffer itself is left to the caller.
+///
+/// ```
+/// use smallset::SmallSetRef;
+/// use std::mem::MaybeUninit;
+///
+/// let mut buf = [MaybeUninit::<u32>::uninit(); 2];
+/// let mut s = SmallSetRef::new(&mut buf);
+/// assert!(s.insert(1) == Ok(true));
+/// assert!(s.insert(1) == Ok(false));
+/// assert!(s.insert(2) == Ok(true));
+/// assert!(s.insert(3).is_err());
+/// assert!(s.as_slice() == [1, 2]);
+/// ```
+pub struct SmallSetRef<'a, T: 'a> {
+    buf: &'a mut [MaybeUninit<T>],
+    len: usize,
+}
+
+impl<'a, T: PartialEq + Eq> SmallSetRef<'a, T> {
+    /// Creates an empty set using `buf` as storage. The set can hold at most
+    /// `buf.len()` elements.
+    #[inline]
+    pub fn new(buf: &'a mut [MaybeUninit<T>]) -> SmallSetRef<'a, T> {
+        SmallSetRef { buf, len: 0 }
+    }
+
+    /// Inserts `elem` if not yet present. Returns `Ok(true)` if the element
+    /// was inserted and `Ok(false)` if it was already present. If the element
+    /// is new but the buffer is full, the set is left unchanged and `elem` is
+    /// handed back inside the error.
+    pub fn insert(&mut self, elem: T) -> Result<bool, CapacityError<T>> {
+        if self.contains(&elem) {
+            return Ok(false);
+        }
+        if self.len == self.buf.len() {
+            return Err(CapacityError::new(elem));
+        }
+        self.buf[self.len] = MaybeUninit::new(elem);
+        self.len += 1;
+        Ok(true)
+    }
+
+    /// Removes `elem` from the set, preserving the order of the remaining
+    /// elements. Returns `true` if the element was removed.
+    pub fn remove(&mut self, elem: &T) -> bool {
+        let pos = match self.iter().position(|e| *e == *elem) {
+            Some(pos) => pos,
+            None => return false,
+        };
+        // Move the element out and close the gap before dropping it, so that
+        // the set is consistent even if `T::drop` panics.
+        let removed = unsafe {
+            let base = self.buf.as_mut_ptr() as *mut T;
+            let removed = ptr::read(base.add(pos));
+            ptr::copy(base.add(pos + 1), base.add(pos), self.len - pos - 1);
+            removed
+        };
+        self.len -= 1;
+        drop(removed);
+        true
+    }
+
+    /// Tests whether `elem` is present.
+    #[inline]
+    pub fn contains(&self, elem: &T) -> bool {
+        self.as_slice().contains(elem)
+    }
+
+    /// Returns the elements as a slice, in insertion order.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.buf.as_ptr() as *const T, self.len) }
+    }
+
+    /// Returns an iterator over the elements, in insertion order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.as_slice().iter()
+    }
+
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of elements the buffer can hold.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Removes and drops all elements.
+    pub fn clear(&mut self) {
+        let len = self.len;
+        self.len = 0;
+        unsafe { drop_prefix(self.buf, len) }
+    }
+}
+
+impl<'a, T> Drop for SmallSetRef<'a, T> {
+    fn drop(&mut self) {
+        unsafe { drop_prefix(self.buf, self.len) }
+    }
+}
+
+/// Drops the first `len` slots of `buf`, which must be initialized.
+unsafe fn drop_prefix<T>(buf: &mut [MaybeUninit<T>], len: usize) {
+    ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+        buf.as_mut_ptr() as *mut T,
+        len,
+    ));
+}
+
+impl<'a, T: PartialEq + Eq + fmt::Debug> fmt::Debug for SmallSetRef<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_drop() {
+        let token = Rc::new(0);
+        let mut buf: [MaybeUninit<Rc<u32>>; 3] = [
+            MaybeUninit::uninit(),
+            MaybeUninit::uninit(),
+            MaybeUninit::uninit(),
+        ];
+        {
+            let mut s = SmallSetRef::new(&mut buf);
+            let a = Rc::new(1);
+            assert!(s.insert(token.clone()) == Ok(true));
+            assert!(s.insert(a.clone()) == Ok(true));
+            assert!(s.insert(Rc::new(1)) == Ok(false));
+            assert!(s.remove(&a) && !s.remove(&a));
+            assert!(Rc::strong_count(&a) == 1);
+            assert!(s.len() == 1 && s.capacity() == 3);
+            s.clear();
+            assert!(s.is_empty() && Rc::strong_count(&token) == 1);
+            s.insert(token.clone()).unwrap();
+            assert!(Rc::strong_count(&token) == 2);
+        }
+        assert!(Rc::strong_count(&token) == 1);
+    }
+
+    #[test]
+    fn test_order() {
+        let mut buf = [MaybeUninit::<u8>::uninit(); 4];
+        let mut s = SmallSetRef::new(&mut buf);
+        for i in 1..5 {
+            s.insert(i).unwrap();
+        }
+        assert!(s.insert(5).unwrap_err().into_element() == 5);
+        s.remove(&2);
+        assert!(s.iter().copied().collect::<Vec<u8>>() == vec![1, 3, 4]);
+        s.insert(9).unwrap();
+        assert!(format!("{:?}", s) == "[1, 3, 4, 9]");
+    }
+}
@@ -0,0 +1,183 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! An interior-mutable small set of `Copy` elements.
+
+use smallvec::Array;
+use std::cell::Cell;
+use std::fmt;
+
+use SmallSet;
+
+/// A `SmallSetCell` is a `SmallSet` of `Copy` elements that can be modified
+/// through a shared reference, in the manner of `std::cell::Cell`. Unlike a
+/// `RefCell<SmallSet<A>>`, there is no borrow flag to check at runtime and no
+/// way to panic on a conflicting borrow: each operation briefly moves the set
+/// out of the cell, works on it, and moves it back.
+///
+/// This is intended for markers stored inside otherwise-shared structures,
+/// such as the "visited by" set of a graph node. Like `Cell`, it is not
+/// `Sync`.
+///
+/// ```
+/// use smallset::SmallSetCell;
+///
+/// let visited: SmallSetCell<[u32; 4]> = SmallSetCell::new();
+/// assert!(visited.insert(3));
+/// assert!(!visited.insert(3));
+/// assert!(visited.contains(&3));
+/// ```
+pub struct SmallSetCell<A: Array> {
+    set: Cell<SmallSet<A>>,
+}
+
+impl<A: Array> SmallSetCell<A>
+where
+    A::Item: PartialEq + Eq + Copy,
+{
+    /// Creates a new, empty `SmallSetCell`.
+    #[inline]
+    pub fn new() -> SmallSetCell<A> {
+        SmallSetCell::from_set(SmallSet::new())
+    }
+
+    /// Creates a `SmallSetCell` whose initial contents are `set`.
+    #[inline]
+    pub fn from_set(set: SmallSet<A>) -> SmallSetCell<A> {
+        SmallSetCell {
+            set: Cell::new(set),
+        }
+    }
+
+    /// Runs `f` on the contained set. While `f` runs, the cell holds an empty
+    /// set, so this must not be called reentrantly from `f` or from the
+    /// elements' `PartialEq` impl; doing so is safe but observes an empty set.
+    #[inline]
+    fn with<R, F: FnOnce(&mut SmallSet<A>) -> R>(&self, f: F) -> R {
+        let mut set = self.set.take();
+        let r = f(&mut set);
+        self.set.set(set);
+        r
+    }
+
+    /// Inserts `elem` if not yet present. Returns `true` if the element was
+    /// inserted.
+    #[inline]
+    pub fn insert(&self, elem: A::Item) -> bool {
+        self.with(|s| s.insert(elem))
+    }
+
+    /// Removes `elem`. Returns `true` if the element was removed.
+    #[inline]
+    pub fn remove(&self, elem: &A::Item) -> bool {
+        self.with(|s| s.remove(elem))
+    }
+
+    /// Tests whether `elem` is present.
+    #[inline]
+    pub fn contains(&self, elem: &A::Item) -> bool {
+        self.with(|s| s.contains(elem))
+    }
+
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.with(|s| s.len())
+    }
+
+    /// Returns `true` if the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.with(|s| s.is_empty())
+    }
+
+    /// Removes all elements from the set.
+    #[inline]
+    pub fn clear(&self) {
+        self.with(|s| s.clear())
+    }
+
+    /// Returns a copy of the contained set.
+    pub fn get(&self) -> SmallSet<A> {
+        self.with(|s| s.clone())
+    }
+
+    /// Takes the contained set, leaving an empty set in its place.
+    pub fn take(&self) -> SmallSet<A> {
+        self.set.take()
+    }
+
+    /// Returns a mutable reference to the contained set. No other reference
+    /// to the cell can exist at the same time, so this is free.
+    pub fn get_mut(&mut self) -> &mut SmallSet<A> {
+        self.set.get_mut()
+    }
+
+    /// Consumes the cell, returning the contained set.
+    pub fn into_inner(self) -> SmallSet<A> {
+        self.set.into_inner()
+    }
+}
+
+impl<A: Array> Default for SmallSetCell<A>
+where
+    A::Item: PartialEq + Eq + Copy,
+{
+    fn default() -> SmallSetCell<A> {
+        SmallSetCell::new()
+    }
+}
+
+impl<A: Array> Clone for SmallSetCell<A>
+where
+    A::Item: PartialEq + Eq + Copy,
+{
+    fn clone(&self) -> SmallSetCell<A> {
+        SmallSetCell::from_set(self.get())
+    }
+}
+
+impl<A: Array> fmt::Debug for SmallSetCell<A>
+where
+    A::Item: PartialEq + Eq + Copy + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    struct Node {
+        visited_by: SmallSetCell<[u32; 2]>,
+    }
+
+    #[test]
+    fn test_shared_nodes() {
+        let nodes = [
+            Node {
+                visited_by: SmallSetCell::new(),
+            },
+            Node {
+                visited_by: SmallSetCell::new(),
+            },
+        ];
+        let shared: Vec<&Node> = vec![&nodes[0], &nodes[1], &nodes[0]];
+        for (walk, node) in shared.iter().enumerate() {
+            assert!(node.visited_by.insert(walk as u32));
+        }
+        assert!(nodes[0].visited_by.len() == 2);
+        assert!(nodes[0].visited_by.contains(&2));
+        assert!(nodes[1].visited_by.remove(&1));
+        assert!(nodes[1].visited_by.is_empty());
+        let set = nodes[0].visited_by.get();
+        assert!(set.iter().copied().collect::<Vec<u32>>() == vec![0, 2]);
+        assert!(format!("{:?}", nodes[0].visited_by) == "[0, 2]");
+    }
+}
@@ -0,0 +1,115 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! `clap` support, enabled with the `clap` feature.
+
+use clap::error::ErrorKind;
+use clap::{ArgMatches, Error};
+use smallvec::Array;
+
+use SmallSet;
+
+/// What `SmallSet::from_arg_matches` does when the same value is given more
+/// than once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Keep the first occurrence and silently drop the others.
+    Ignore,
+    /// Fail with a `clap` validation error naming the repeated value.
+    Reject,
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Clone + Send + Sync + 'static,
+{
+    /// Collects the values of the argument `id` into a set, in the order in
+    /// which they were given on the command line. This is intended for
+    /// repeated options, such as `--feature x --feature y`, defined with
+    /// `ArgAction::Append` and a value parser producing `A::Item`. An absent
+    /// argument yields an empty set.
+    ///
+    /// ```
+    /// extern crate clap;
+    /// # extern crate smallset;
+    /// use clap::{Arg, ArgAction, Command};
+    /// use smallset::{DuplicatePolicy, SmallSet};
+    ///
+    /// let cmd = Command::new("app").arg(
+    ///     Arg::new("feature").long("feature").action(ArgAction::Append),
+    /// );
+    /// let m = cmd.get_matches_from(["app", "--feature", "x", "--feature", "y"]);
+    /// let features: SmallSet<[String; 4]> =
+    ///     SmallSet::from_arg_matches(&m, "feature", DuplicatePolicy::Reject).unwrap();
+    /// assert!(features.len() == 2);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics, like `ArgMatches::get_many`, if `id` is not a defined argument
+    /// or its values are not of type `A::Item`.
+    pub fn from_arg_matches(
+        matches: &ArgMatches,
+        id: &str,
+        policy: DuplicatePolicy,
+    ) -> Result<SmallSet<A>, Error> {
+        let mut set = SmallSet::new();
+        let values = match matches.get_many::<A::Item>(id) {
+            Some(values) => values,
+            None => return Ok(set),
+        };
+        for (i, value) in values.enumerate() {
+            if !set.insert(value.clone()) && policy == DuplicatePolicy::Reject {
+                let raw = matches
+                    .get_raw(id)
+                    .and_then(|mut raw| raw.nth(i))
+                    .map(|raw| raw.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                return Err(Error::raw(
+                    ErrorKind::ValueValidation,
+                    format!(
+                        "the value '{}' was given more than once for '{}'\n",
+                        raw, id
+                    ),
+                ));
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::{value_parser, Arg, ArgAction, Command};
+
+    fn command() -> Command {
+        Command::new("app").arg(
+            Arg::new("level")
+                .long("level")
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(u32)),
+        )
+    }
+
+    #[test]
+    fn test_from_arg_matches() {
+        let m = command().get_matches_from(["app", "--level", "3", "--level", "1", "--level", "3"]);
+        let s: SmallSet<[u32; 4]> =
+            SmallSet::from_arg_matches(&m, "level", DuplicatePolicy::Ignore).unwrap();
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 1]);
+
+        let e = SmallSet::<[u32; 4]>::from_arg_matches(&m, "level", DuplicatePolicy::Reject)
+            .unwrap_err();
+        assert!(e.kind() == ErrorKind::ValueValidation);
+        assert!(e.to_string().contains("'3'"));
+
+        let m = command().get_matches_from(["app"]);
+        let s: SmallSet<[u32; 4]> =
+            SmallSet::from_arg_matches(&m, "level", DuplicatePolicy::Reject).unwrap();
+        assert!(s.is_empty());
+    }
+}
@@ -0,0 +1,200 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A set that moves back to inline storage automatically after removals.
+
+use smallvec::Array;
+use std::fmt;
+use std::mem;
+use std::ops::Deref;
+
+use SmallSet;
+
+/// A `DemotingSmallSet` wraps a `SmallSet` and, once it has spilled to the
+/// heap, moves it back inline as soon as removals bring its length down to
+/// `percent` percent of the inline capacity. Since the set only spills again
+/// when it outgrows the inline array, the gap between the two thresholds
+/// keeps a set whose size hovers around the inline capacity from moving back
+/// and forth on every insert and remove.
+///
+/// The wrapped set can be read through `Deref`; changes go through the
+/// wrapper so that the policy is applied.
+///
+/// ```
+/// use smallset::DemotingSmallSet;
+///
+/// // Move back inline at 2 or fewer elements.
+/// let mut s: DemotingSmallSet<[u32; 4]> = DemotingSmallSet::new(50);
+/// for i in 0..6 {
+///     s.insert(i);
+/// }
+/// assert!(s.spilled());
+/// s.retain(|&e| e < 3);
+/// assert!(s.spilled());
+/// s.remove(&2);
+/// assert!(!s.spilled());
+/// ```
+pub struct DemotingSmallSet<A: Array>
+where
+    A::Item: PartialEq + Eq,
+{
+    set: SmallSet<A>,
+    demote_at: usize,
+}
+
+impl<A: Array> DemotingSmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    /// Creates a new, empty set that moves back inline once its length is at
+    /// most `percent` percent of the inline capacity, rounded down. Values of
+    /// `percent` above 100 are treated as 100.
+    #[inline]
+    pub fn new(percent: usize) -> DemotingSmallSet<A> {
+        DemotingSmallSet::from_set(SmallSet::new(), percent)
+    }
+
+    /// Wraps `set` with the policy of `new`, demoting it right away if it
+    /// already qualifies.
+    pub fn from_set(set: SmallSet<A>, percent: usize) -> DemotingSmallSet<A> {
+        // Split the product so that it cannot overflow: zero-sized elements
+        // report an inline size of `usize::MAX`.
+        let inline = set.elements.inline_size();
+        let percent = percent.min(100);
+        let mut s = DemotingSmallSet {
+            set,
+            demote_at: inline / 100 * percent + inline % 100 * percent / 100,
+        };
+        s.maybe_demote();
+        s
+    }
+
+    #[inline]
+    fn maybe_demote(&mut self) {
+        if self.set.elements.spilled() && self.set.len() <= self.demote_at {
+            self.set.shrink_to_fit();
+        }
+    }
+
+    /// Inserts `elem` if not yet present. Returns `true` if the element was
+    /// inserted.
+    #[inline]
+    pub fn insert(&mut self, elem: A::Item) -> bool {
+        self.set.insert(elem)
+    }
+
+    /// Removes `elem`, then moves the set back inline if the policy says so.
+    /// Returns `true` if the element was removed.
+    pub fn remove(&mut self, elem: &A::Item) -> bool {
+        let removed = self.set.remove(elem);
+        self.maybe_demote();
+        removed
+    }
+
+    /// Keeps only the elements for which `pred` returns `true`, in order, then
+    /// moves the set back inline if the policy says so.
+    pub fn retain<F: FnMut(&A::Item) -> bool>(&mut self, pred: F) {
+        self.set.retain(pred);
+        self.maybe_demote();
+    }
+
+    /// Removes and returns all elements, in order, leaving the set empty and
+    /// inline.
+    pub fn drain(&mut self) -> impl Iterator<Item = A::Item> {
+        mem::take(&mut self.set).into_iter()
+    }
+
+    /// Clears the set, returning it to inline storage.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.set = SmallSet::new();
+    }
+
+    /// Returns `true` if the set is stored on the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        self.set.elements.spilled()
+    }
+
+    /// Unwraps the set.
+    #[inline]
+    pub fn into_inner(self) -> SmallSet<A> {
+        self.set
+    }
+}
+
+impl<A: Array> Deref for DemotingSmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    type Target = SmallSet<A>;
+
+    #[inline]
+    fn deref(&self) -> &SmallSet<A> {
+        &self.set
+    }
+}
+
+impl<A: Array> fmt::Debug for DemotingSmallSet<A>
+where
+    A::Item: PartialEq + Eq + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.set.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_hysteresis() {
+        let mut s: DemotingSmallSet<[u32; 4]> = DemotingSmallSet::new(50);
+        for i in 0..5 {
+            s.insert(i);
+        }
+        assert!(s.spilled());
+        // Back at the inline capacity, but above the threshold: no demotion.
+        s.remove(&4);
+        s.remove(&3);
+        assert!(s.spilled() && s.len() == 3);
+        s.remove(&2);
+        assert!(!s.spilled());
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![0, 1]);
+        assert!(s.contains(&1));
+
+        let set: SmallSet<[u32; 4]> = (0..2).chain(10..20).collect();
+        let mut t = DemotingSmallSet::from_set(set, 100);
+        assert!(t.spilled());
+        t.retain(|&e| e >= 16);
+        assert!(!t.spilled() && t.len() == 4);
+        assert!(t.drain().collect::<Vec<u32>>() == vec![16, 17, 18, 19]);
+        assert!(t.is_empty());
+        let set = t.into_inner();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_from_set_demotes() {
+        let mut set: SmallSet<[u32; 4]> = (0..8).collect();
+        set.retain_top_k_by(1, |&e| e);
+        let s = DemotingSmallSet::from_set(set, 25);
+        assert!(!s.spilled());
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        // smallvec reports an inline size of `usize::MAX` for these.
+        let mut s: DemotingSmallSet<[(); 4]> = DemotingSmallSet::new(50);
+        assert!(s.demote_at > usize::MAX / 3);
+        assert!(s.insert(()) && !s.insert(()));
+        assert!(s.remove(&()) && !s.spilled());
+        let t: DemotingSmallSet<[(); 4]> = DemotingSmallSet::new(100);
+        assert!(t.demote_at == usize::MAX);
+    }
+}
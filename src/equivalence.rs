@@ -0,0 +1,204 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A small set with a caller-chosen notion of element equality.
+
+use smallvec::{Array, SmallVec};
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::slice::Iter;
+
+/// An equality strategy for `SmallSetBy`. Implementations are usually
+/// zero-sized marker types, so choosing one costs nothing at runtime.
+///
+/// `equivalent` should behave like an equivalence relation (reflexive,
+/// symmetric and transitive). If it does not, as with a float tolerance, the
+/// set still works, but which of several "close" elements it keeps depends on
+/// insertion order.
+pub trait Equivalence<T: ?Sized> {
+    /// Returns `true` if `a` and `b` should be treated as the same element.
+    fn equivalent(a: &T, b: &T) -> bool;
+}
+
+/// Compares string-like elements ignoring ASCII case.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsciiCaseInsensitive;
+
+impl<T: AsRef<str> + ?Sized> Equivalence<T> for AsciiCaseInsensitive {
+    #[inline]
+    fn equivalent(a: &T, b: &T) -> bool {
+        a.as_ref().eq_ignore_ascii_case(b.as_ref())
+    }
+}
+
+/// A `SmallSetBy` is a `SmallSet` whose elements are compared with the
+/// strategy `E` instead of `PartialEq`, so domain-specific equality (such as
+/// case-insensitive strings) does not require a newtype around every element.
+///
+/// When an element equivalent to one already present is inserted, the stored
+/// element is kept and the new one is dropped.
+///
+/// ```
+/// use smallset::{AsciiCaseInsensitive, SmallSetBy};
+///
+/// let mut s: SmallSetBy<[&str; 4], AsciiCaseInsensitive> = SmallSetBy::new();
+/// assert!(s.insert("Content-Type"));
+/// assert!(!s.insert("content-type"));
+/// assert!(s.contains(&"CONTENT-TYPE"));
+/// ```
+pub struct SmallSetBy<A: Array, E> {
+    elements: SmallVec<A>,
+    _eq: PhantomData<E>,
+}
+
+impl<A: Array, E: Equivalence<A::Item>> SmallSetBy<A, E> {
+    /// Creates a new, empty `SmallSetBy`.
+    #[inline]
+    pub fn new() -> SmallSetBy<A, E> {
+        SmallSetBy {
+            elements: SmallVec::new(),
+            _eq: PhantomData,
+        }
+    }
+
+    /// Inserts `elem` into the set if no equivalent element is present.
+    /// Returns `true` if the element was inserted.
+    #[inline]
+    pub fn insert(&mut self, elem: A::Item) -> bool {
+        if self.contains(&elem) {
+            false
+        } else {
+            self.elements.push(elem);
+            true
+        }
+    }
+
+    /// Removes the element equivalent to `elem`, returning it, or `None` if
+    /// there is no such element.
+    #[inline]
+    pub fn remove(&mut self, elem: &A::Item) -> Option<A::Item> {
+        self.position(elem).map(|pos| self.elements.remove(pos))
+    }
+
+    /// Tests whether an element equivalent to `elem` is present.
+    #[inline]
+    pub fn contains(&self, elem: &A::Item) -> bool {
+        self.position(elem).is_some()
+    }
+
+    /// Returns the stored element equivalent to `elem`, if any.
+    #[inline]
+    pub fn get(&self, elem: &A::Item) -> Option<&A::Item> {
+        self.position(elem).map(|pos| &self.elements[pos])
+    }
+
+    /// Returns an iterator over the set elements, in insertion order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, A::Item> {
+        self.elements.iter()
+    }
+
+    /// Returns the current length of the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Clears the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    fn position(&self, elem: &A::Item) -> Option<usize> {
+        self.elements.iter().position(|e| E::equivalent(e, elem))
+    }
+}
+
+impl<A: Array, E: Equivalence<A::Item>> Default for SmallSetBy<A, E> {
+    #[inline]
+    fn default() -> SmallSetBy<A, E> {
+        SmallSetBy::new()
+    }
+}
+
+impl<A: Array, E> Clone for SmallSetBy<A, E>
+where
+    A::Item: Clone,
+{
+    fn clone(&self) -> SmallSetBy<A, E> {
+        SmallSetBy {
+            elements: self.elements.clone(),
+            _eq: PhantomData,
+        }
+    }
+}
+
+impl<A: Array, E> fmt::Debug for SmallSetBy<A, E>
+where
+    A::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.elements.fmt(f)
+    }
+}
+
+impl<A: Array, E: Equivalence<A::Item>> FromIterator<A::Item> for SmallSetBy<A, E> {
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = A::Item>,
+    {
+        let mut set = SmallSetBy::new();
+        for elem in iter {
+            set.insert(elem);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::string::{String, ToString};
+    use std::vec::Vec;
+
+    struct Tolerance;
+
+    impl Equivalence<f64> for Tolerance {
+        fn equivalent(a: &f64, b: &f64) -> bool {
+            (a - b).abs() < 0.01
+        }
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        let mut s: SmallSetBy<[String; 2], AsciiCaseInsensitive> = vec!["a", "B", "A", "c"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert!(s.len() == 3);
+        assert!(s.get(&"b".to_string()).map(|e| e.as_str()) == Some("B"));
+        assert!(s.remove(&"C".to_string()) == Some("c".to_string()));
+        assert!(s.iter().map(|e| e.as_str()).collect::<Vec<&str>>() == vec!["a", "B"]);
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let mut s: SmallSetBy<[f64; 4], Tolerance> = SmallSetBy::new();
+        assert!(s.insert(1.0));
+        assert!(!s.insert(1.001));
+        assert!(s.insert(1.1));
+        assert!(s.contains(&1.099));
+        assert!(s.len() == 2);
+    }
+}
@@ -0,0 +1,329 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Error types returned by the fallible `SmallSet` operations.
+//!
+//! Each error carries the element that caused it, so that the caller gets
+//! ownership of the value back rather than having it silently dropped. The
+//! individual error types can be converted into the catch-all `Error` enum
+//! with `?` or `From`.
+
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The set could not make room for an element, either because it is not
+/// allowed to allocate or because the allocation itself failed.
+///
+/// For operations that do not consume an element (such as reserving space),
+/// the payload is `()`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct CapacityError<T = ()> {
+    element: T,
+}
+
+impl<T> CapacityError<T> {
+    /// Creates a new `CapacityError` carrying `element`.
+    pub fn new(element: T) -> CapacityError<T> {
+        CapacityError { element }
+    }
+
+    /// Returns a reference to the element that could not be stored.
+    pub fn element(&self) -> &T {
+        &self.element
+    }
+
+    /// Consumes the error, returning the element that could not be stored.
+    pub fn into_element(self) -> T {
+        self.element
+    }
+
+    /// Drops the element, leaving an error without a payload.
+    pub fn simplify(self) -> CapacityError {
+        CapacityError { element: () }
+    }
+}
+
+impl<T> fmt::Debug for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("CapacityError: insufficient capacity")
+    }
+}
+
+impl<T> fmt::Display for CapacityError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("insufficient capacity")
+    }
+}
+
+impl<T> error::Error for CapacityError<T> {}
+
+/// The element being inserted was already present in the set.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct OccupiedError<T> {
+    element: T,
+}
+
+impl<T> OccupiedError<T> {
+    /// Creates a new `OccupiedError` carrying `element`.
+    pub fn new(element: T) -> OccupiedError<T> {
+        OccupiedError { element }
+    }
+
+    /// Returns a reference to the element that was not inserted.
+    pub fn element(&self) -> &T {
+        &self.element
+    }
+
+    /// Consumes the error, returning the element that was not inserted.
+    pub fn into_element(self) -> T {
+        self.element
+    }
+}
+
+impl<T> fmt::Debug for OccupiedError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("OccupiedError: element already present")
+    }
+}
+
+impl<T> fmt::Display for OccupiedError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("element already present")
+    }
+}
+
+impl<T> error::Error for OccupiedError<T> {}
+
+/// A sequence of elements that was required to be distinct contained the
+/// same element more than once. The payload is the repeated element.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct DuplicateError<T> {
+    element: T,
+}
+
+impl<T> DuplicateError<T> {
+    /// Creates a new `DuplicateError` carrying `element`.
+    pub fn new(element: T) -> DuplicateError<T> {
+        DuplicateError { element }
+    }
+
+    /// Returns a reference to the repeated element.
+    pub fn element(&self) -> &T {
+        &self.element
+    }
+
+    /// Consumes the error, returning the repeated element.
+    pub fn into_element(self) -> T {
+        self.element
+    }
+}
+
+impl<T> fmt::Debug for DuplicateError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("DuplicateError: duplicate element")
+    }
+}
+
+impl<T> fmt::Display for DuplicateError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("duplicate element")
+    }
+}
+
+impl<T> error::Error for DuplicateError<T> {}
+
+/// An element was outside the range that the requested representation can
+/// hold, such as an integer too large for a bitmask.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RangeError<T> {
+    element: T,
+}
+
+impl<T> RangeError<T> {
+    /// Creates a new `RangeError` carrying `element`.
+    pub fn new(element: T) -> RangeError<T> {
+        RangeError { element }
+    }
+
+    /// Returns a reference to the out-of-range element.
+    pub fn element(&self) -> &T {
+        &self.element
+    }
+
+    /// Consumes the error, returning the out-of-range element.
+    pub fn into_element(self) -> T {
+        self.element
+    }
+}
+
+impl<T> fmt::Debug for RangeError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RangeError: element out of range")
+    }
+}
+
+impl<T> fmt::Display for RangeError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("element out of range")
+    }
+}
+
+impl<T> error::Error for RangeError<T> {}
+
+/// A set was required to hold exactly one element, but held none or several.
+///
+/// For operations that consume the set, the payload is the set, handed back
+/// unchanged; otherwise it is `()`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct NotSingleError<T = ()> {
+    len: usize,
+    inner: T,
+}
+
+impl<T> NotSingleError<T> {
+    /// Creates a new `NotSingleError` for a set of `len` elements, carrying
+    /// `inner`.
+    pub fn new(len: usize, inner: T) -> NotSingleError<T> {
+        NotSingleError { len, inner }
+    }
+
+    /// Returns the number of elements the set actually held.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set was empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Consumes the error, returning its payload.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> fmt::Debug for NotSingleError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NotSingleError: {}", self)
+    }
+}
+
+impl<T> fmt::Display for NotSingleError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "expected exactly one element, found {}", self.len)
+    }
+}
+
+impl<T> error::Error for NotSingleError<T> {}
+
+/// A delimited string could not be parsed into a set, either because one of
+/// its items is invalid or because an item appears more than once.
+#[derive(Clone, PartialEq, Eq)]
+pub enum ParseError<T, E> {
+    /// An item failed to parse. The payload is the item's parse error.
+    Invalid(E),
+    /// An item appeared more than once.
+    Duplicate(DuplicateError<T>),
+}
+
+/// The error returned when parsing a set of `T` from a delimited string.
+pub type ParseDelimitedError<T> = ParseError<T, <T as FromStr>::Err>;
+
+impl<T, E: fmt::Debug> fmt::Debug for ParseError<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Invalid(ref e) => write!(f, "ParseError: invalid item: {:?}", e),
+            ParseError::Duplicate(ref e) => write!(f, "ParseError: {:?}", e),
+        }
+    }
+}
+
+impl<T, E: fmt::Display> fmt::Display for ParseError<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Invalid(ref e) => write!(f, "invalid item: {}", e),
+            ParseError::Duplicate(ref e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl<T, E: fmt::Debug + fmt::Display> error::Error for ParseError<T, E> {}
+
+/// Any of the errors that carry an element, for callers that combine several
+/// fallible operations and want to propagate them with a single error type.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Error<T> {
+    /// See `CapacityError`.
+    Capacity(CapacityError<T>),
+    /// See `OccupiedError`.
+    Occupied(OccupiedError<T>),
+    /// See `DuplicateError`.
+    Duplicate(DuplicateError<T>),
+    /// See `RangeError`.
+    Range(RangeError<T>),
+}
+
+impl<T> Error<T> {
+    /// Consumes the error, returning the element that caused it.
+    pub fn into_element(self) -> T {
+        match self {
+            Error::Capacity(e) => e.into_element(),
+            Error::Occupied(e) => e.into_element(),
+            Error::Duplicate(e) => e.into_element(),
+            Error::Range(e) => e.into_element(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Error<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Capacity(ref e) => fmt::Debug::fmt(e, f),
+            Error::Occupied(ref e) => fmt::Debug::fmt(e, f),
+            Error::Duplicate(ref e) => fmt::Debug::fmt(e, f),
+            Error::Range(ref e) => fmt::Debug::fmt(e, f),
+        }
+    }
+}
+
+impl<T> fmt::Display for Error<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Capacity(ref e) => fmt::Display::fmt(e, f),
+            Error::Occupied(ref e) => fmt::Display::fmt(e, f),
+            Error::Duplicate(ref e) => fmt::Display::fmt(e, f),
+            Error::Range(ref e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl<T> error::Error for Error<T> {}
+
+impl<T> From<CapacityError<T>> for Error<T> {
+    fn from(e: CapacityError<T>) -> Error<T> {
+        Error::Capacity(e)
+    }
+}
+
+impl<T> From<OccupiedError<T>> for Error<T> {
+    fn from(e: OccupiedError<T>) -> Error<T> {
+        Error::Occupied(e)
+    }
+}
+
+impl<T> From<DuplicateError<T>> for Error<T> {
+    fn from(e: DuplicateError<T>) -> Error<T> {
+        Error::Duplicate(e)
+    }
+}
+
+impl<T> From<RangeError<T>> for Error<T> {
+    fn from(e: RangeError<T>) -> Error<T> {
+        Error::Range(e)
+    }
+}
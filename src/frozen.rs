@@ -0,0 +1,288 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A read-only sorted set over a static slice, constructible at compile time.
+
+use smallvec::Array;
+use std::fmt;
+use std::slice::Iter;
+
+use SmallSet;
+
+/// A `FrozenSmallSet` is an immutable set whose elements live in a
+/// `&'static` slice. Construction is a `const fn`, so a frozen set can be
+/// placed in a `static` or `const` with no runtime initialization; the
+/// `static_smallset!` macro is the usual way to build one.
+///
+/// The elements must be given in strictly ascending order, that is, sorted
+/// and without duplicates, so that `contains` can binary search them. Lookups
+/// do not check this. It is checked once, when the set is built, by
+/// `from_static_checked` and the typed form of `static_smallset!` for
+/// primitive and `&str` elements, at compile time in a `static` or `const`,
+/// and by `try_from_static` for any `Ord` element at run time. `from_static`
+/// and the untyped macro form take the order on trust: with unsorted elements,
+/// `contains` gives wrong answers.
+///
+/// ```
+/// #[macro_use]
+/// extern crate smallset;
+/// use smallset::FrozenSmallSet;
+///
+/// // Sorted, as `contains` requires; the `&str` prefix checks it.
+/// static KEYWORDS: FrozenSmallSet<&str> = static_smallset![&str; "fn", "let", "mut"];
+///
+/// fn main() {
+///     assert!(KEYWORDS.contains(&"let"));
+///     assert!(!KEYWORDS.contains(&"var"));
+/// }
+/// ```
+pub struct FrozenSmallSet<T: 'static> {
+    elements: &'static [T],
+}
+
+impl<T> FrozenSmallSet<T> {
+    /// Creates a frozen set of the elements of `elements`, which must be in
+    /// strictly ascending order. The order is not checked; see
+    /// `from_static_checked` and `try_from_static`.
+    #[inline]
+    pub const fn from_static(elements: &'static [T]) -> FrozenSmallSet<T> {
+        FrozenSmallSet { elements }
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the elements as a slice, in ascending order.
+    #[inline]
+    pub const fn as_slice(&self) -> &'static [T] {
+        self.elements
+    }
+
+    /// Returns an iterator over the elements, in ascending order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'static, T> {
+        self.elements.iter()
+    }
+}
+
+impl<T: Ord> FrozenSmallSet<T> {
+    /// Creates a frozen set of the elements of `elements`, or returns `None`
+    /// if they are not in strictly ascending order.
+    pub fn try_from_static(elements: &'static [T]) -> Option<FrozenSmallSet<T>> {
+        if is_strictly_ascending(elements) {
+            Some(FrozenSmallSet { elements })
+        } else {
+            None
+        }
+    }
+
+    /// Tests whether `elem` is present, by binary search.
+    #[inline]
+    pub fn contains(&self, elem: &T) -> bool {
+        self.elements.binary_search(elem).is_ok()
+    }
+
+    /// Copies the elements into a new, mutable `SmallSet`, in ascending
+    /// order. Since the elements are distinct, they are copied without
+    /// membership checks.
+    pub fn to_set<A: Array<Item = T>>(&self) -> SmallSet<A>
+    where
+        T: Clone,
+    {
+        debug_assert!(
+            is_strictly_ascending(self.elements),
+            "FrozenSmallSet elements are not in strictly ascending order"
+        );
+        SmallSet {
+            elements: self.elements.iter().cloned().collect(),
+        }
+    }
+}
+
+fn is_strictly_ascending<T: Ord>(elements: &[T]) -> bool {
+    elements.windows(2).all(|w| w[0] < w[1])
+}
+
+macro_rules! impl_from_static_checked {
+    ($($t:ty),*) => {$(
+        impl FrozenSmallSet<$t> {
+            /// Creates a frozen set of the elements of `elements`, panicking if
+            /// they are not in strictly ascending order. In a `static` or
+            /// `const` initializer, the panic is a compile error.
+            pub const fn from_static_checked(elements: &'static [$t]) -> FrozenSmallSet<$t> {
+                let mut i = 1;
+                while i < elements.len() {
+                    assert!(
+                        elements[i - 1] < elements[i],
+                        "FrozenSmallSet elements are not in strictly ascending order"
+                    );
+                    i += 1;
+                }
+                FrozenSmallSet { elements }
+            }
+        }
+    )*};
+}
+
+impl_from_static_checked!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, char);
+
+impl FrozenSmallSet<&'static str> {
+    /// Creates a frozen set of the elements of `elements`, panicking if they
+    /// are not in strictly ascending order. In a `static` or `const`
+    /// initializer, the panic is a compile error.
+    ///
+    /// ```compile_fail
+    /// use smallset::FrozenSmallSet;
+    ///
+    /// static WORDS: FrozenSmallSet<&str> = FrozenSmallSet::<&str>::from_static_checked(&["b", "a"]);
+    /// ```
+    pub const fn from_static_checked(
+        elements: &'static [&'static str],
+    ) -> FrozenSmallSet<&'static str> {
+        let mut i = 1;
+        while i < elements.len() {
+            assert!(
+                str_lt(elements[i - 1], elements[i]),
+                "FrozenSmallSet elements are not in strictly ascending order"
+            );
+            i += 1;
+        }
+        FrozenSmallSet { elements }
+    }
+}
+
+/// `a < b` for strings, which `Ord` cannot provide in a `const fn`.
+const fn str_lt(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let mut i = 0;
+    while i < a.len() && i < b.len() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+        i += 1;
+    }
+    a.len() < b.len()
+}
+
+impl<T> Clone for FrozenSmallSet<T> {
+    fn clone(&self) -> FrozenSmallSet<T> {
+        *self
+    }
+}
+
+impl<T> Copy for FrozenSmallSet<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for FrozenSmallSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.elements.iter()).finish()
+    }
+}
+
+/// Builds a `FrozenSmallSet` from a list of constant expressions, which must
+/// be in strictly ascending order. The result is a constant expression, so it
+/// can initialize a `static` or `const`.
+///
+/// Prefixing the list with a primitive or `&str` element type, as in
+/// `static_smallset![u32; 2, 3, 5]`, builds the set with
+/// `from_static_checked`, so that elements out of order are a compile error.
+/// Without the type, the order is not checked.
+///
+/// ```
+/// #[macro_use]
+/// extern crate smallset;
+/// use smallset::FrozenSmallSet;
+///
+/// const PRIMES: FrozenSmallSet<u32> = static_smallset![u32; 2, 3, 5, 7];
+///
+/// fn main() {
+///     assert!(PRIMES.len() == 4 && PRIMES.contains(&5));
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[macro_use]
+/// extern crate smallset;
+/// use smallset::FrozenSmallSet;
+///
+/// const PRIMES: FrozenSmallSet<u32> = static_smallset![u32; 2, 5, 3, 7];
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! static_smallset {
+    ($($elem:expr),* $(,)*) => {
+        $crate::FrozenSmallSet::from_static(&[$($elem),*])
+    };
+    ($t:ty; $($elem:expr),* $(,)*) => {
+        $crate::FrozenSmallSet::<$t>::from_static_checked(&[$($elem),*])
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::string::String;
+
+    static EMPTY: FrozenSmallSet<u8> = static_smallset![];
+    static LETTERS: FrozenSmallSet<char> = static_smallset!['x', 'y', 'z',];
+
+    #[test]
+    fn test_frozen() {
+        assert!(EMPTY.is_empty() && !EMPTY.contains(&0));
+        assert!(LETTERS.len() == 3 && LETTERS.contains(&'y'));
+        assert!(LETTERS.iter().collect::<String>() == "xyz");
+        let s: SmallSet<[char; 4]> = LETTERS.to_set();
+        assert!(s.len() == 3 && s.contains(&'z'));
+        assert!(format!("{:?}", LETTERS) == "{'x', 'y', 'z'}");
+        for c in "abwxyz{".chars() {
+            assert!(LETTERS.contains(&c) == ('x'..='z').contains(&c));
+        }
+    }
+
+    #[test]
+    fn test_checked() {
+        static PRIMES: FrozenSmallSet<u32> = static_smallset![u32; 2, 3, 5, 7,];
+        static WORDS: FrozenSmallSet<&str> = static_smallset![&str; "", "a", "ab", "b"];
+        assert!(PRIMES.contains(&5) && !PRIMES.contains(&4));
+        assert!(WORDS.contains(&"ab") && !WORDS.contains(&"aa"));
+        assert!(FrozenSmallSet::try_from_static(&[1, 2, 3]).is_some());
+    }
+
+    #[test]
+    fn test_duplicates_rejected() {
+        assert!(FrozenSmallSet::try_from_static(&[1, 1, 2]).is_none());
+        assert!(!str_lt("a", "a"));
+    }
+
+    #[test]
+    fn test_unsorted_rejected() {
+        assert!(FrozenSmallSet::try_from_static(&[3, 1, 2]).is_none());
+        assert!(!str_lt("b", "a") && !str_lt("ab", "a"));
+    }
+
+    #[test]
+    #[should_panic(expected = "strictly ascending")]
+    fn test_checked_panics() {
+        let elements: &'static [u32] = &[1, 1];
+        FrozenSmallSet::<u32>::from_static_checked(elements);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "strictly ascending")]
+    fn test_to_set_checks() {
+        static UNSORTED: FrozenSmallSet<u32> = static_smallset![3, 1, 2];
+        let _: SmallSet<[u32; 4]> = UNSORTED.to_set();
+    }
+}
@@ -0,0 +1,44 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! `gc` support, enabled with the `gc` feature.
+
+use gc::{Finalize, Trace};
+use smallvec::Array;
+
+use SmallSet;
+
+impl<A: Array> Finalize for SmallSet<A> where A::Item: PartialEq + Eq {}
+
+/// Traces every element, so that a `SmallSet<[Gc<T>; N]>` can be stored
+/// inside a garbage-collected object.
+unsafe impl<A: Array> Trace for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Trace,
+{
+    gc::custom_trace!(this, {
+        for e in this.iter() {
+            mark(e);
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gc::{force_collect, Gc, GcCell};
+
+    #[test]
+    fn test_trace() {
+        let set: SmallSet<[Gc<u32>; 1]> = vec![Gc::new(1), Gc::new(2)].into_iter().collect();
+        let root = Gc::new(GcCell::new(set));
+        force_collect();
+        root.borrow_mut().insert(Gc::new(3));
+        force_collect();
+        let sum: u32 = root.borrow().iter().map(|e| **e).sum();
+        assert!(sum == 6);
+    }
+}
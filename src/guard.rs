@@ -0,0 +1,143 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A debug-only guard asserting that a set does not spill to the heap.
+
+use smallvec::Array;
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
+use std::thread;
+
+use SmallSet;
+
+/// A guard returned by `SmallSet::assert_no_spill`. It gives access to the set
+/// through `Deref` and `DerefMut`, and in debug builds panics if the set,
+/// inline when the guard was created, has moved to the heap. The check runs
+/// each time the set is accessed through the guard and when the guard is
+/// dropped. In release builds the guard does no checking.
+pub struct NoSpillGuard<'a, A: Array + 'a>
+where
+    A::Item: PartialEq + Eq,
+{
+    set: &'a mut SmallSet<A>,
+    was_spilled: bool,
+}
+
+impl<'a, A: Array> NoSpillGuard<'a, A>
+where
+    A::Item: PartialEq + Eq,
+{
+    #[inline]
+    fn check(&self) {
+        debug_assert!(
+            self.was_spilled || !self.set.elements.spilled() || panicking(),
+            "SmallSet spilled to the heap inside an assert_no_spill scope"
+        );
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    /// Returns a guard through which the set can be used, and which panics in
+    /// debug builds if the set spills to the heap while it is alive. This
+    /// turns "this path must not allocate" into an executable assertion.
+    ///
+    /// ```
+    /// use smallset::SmallSet;
+    ///
+    /// let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+    /// {
+    ///     let mut g = s.assert_no_spill();
+    ///     g.insert(1);
+    ///     g.insert(2);
+    ///     // A third insert would spill, and panic in a debug build.
+    /// }
+    /// assert!(s.len() == 2);
+    /// ```
+    pub fn assert_no_spill(&mut self) -> NoSpillGuard<'_, A> {
+        NoSpillGuard {
+            was_spilled: self.elements.spilled(),
+            set: self,
+        }
+    }
+}
+
+impl<'a, A: Array> Deref for NoSpillGuard<'a, A>
+where
+    A::Item: PartialEq + Eq,
+{
+    type Target = SmallSet<A>;
+
+    fn deref(&self) -> &SmallSet<A> {
+        self.check();
+        self.set
+    }
+}
+
+impl<'a, A: Array> DerefMut for NoSpillGuard<'a, A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn deref_mut(&mut self) -> &mut SmallSet<A> {
+        self.check();
+        self.set
+    }
+}
+
+impl<'a, A: Array> Drop for NoSpillGuard<'a, A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn drop(&mut self) {
+        self.check();
+    }
+}
+
+/// Whether the thread is unwinding, in which case a spill is not reported on
+/// top of the original panic. Without `std` this cannot be detected.
+#[cfg(feature = "std")]
+#[inline]
+fn panicking() -> bool {
+    thread::panicking()
+}
+
+#[cfg(not(feature = "std"))]
+#[inline]
+fn panicking() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_no_spill() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        {
+            let mut g = s.assert_no_spill();
+            g.insert(1);
+            g.insert(2);
+            assert!(g.len() == 2);
+        }
+        s.insert(3);
+        // Already spilled: the guard only catches new spills.
+        let mut g = s.assert_no_spill();
+        g.insert(4);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "spilled to the heap")]
+    fn test_spill_panics() {
+        let mut s: SmallSet<[u32; 1]> = SmallSet::new();
+        let mut g = s.assert_no_spill();
+        g.insert(1);
+        g.insert(2);
+    }
+}
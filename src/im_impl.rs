@@ -0,0 +1,79 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Conversions to and from `im::HashSet`, enabled with the `im` feature.
+
+use im::HashSet;
+use smallvec::Array;
+use std::hash::{BuildHasher, Hash};
+
+use SmallSet;
+
+/// Converts a small mutable set into a persistent one, for example at a
+/// snapshot boundary. The elements are moved, not cloned.
+impl<A: Array, S> From<SmallSet<A>> for HashSet<A::Item, S>
+where
+    A::Item: PartialEq + Eq + Hash + Clone,
+    S: BuildHasher + Default,
+{
+    fn from(set: SmallSet<A>) -> HashSet<A::Item, S> {
+        set.elements.into_iter().collect()
+    }
+}
+
+/// Copies a persistent set into a `SmallSet`. The elements are in the
+/// persistent set's iteration order, which depends on its hasher.
+impl<'a, A: Array, S> From<&'a HashSet<A::Item, S>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Hash + Clone,
+    S: BuildHasher,
+{
+    fn from(set: &'a HashSet<A::Item, S>) -> SmallSet<A> {
+        SmallSet {
+            elements: set.iter().cloned().collect(),
+        }
+    }
+}
+
+impl<A: Array, S> From<HashSet<A::Item, S>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Hash + Clone,
+    S: BuildHasher,
+{
+    fn from(set: HashSet<A::Item, S>) -> SmallSet<A> {
+        SmallSet {
+            elements: set.into_iter().collect(),
+        }
+    }
+}
+
+impl<A: Array, S> PartialEq<HashSet<A::Item, S>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Hash + Clone,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &HashSet<A::Item, S>) -> bool {
+        self.len() == other.len() && self.iter().all(|e| other.contains(e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let s: SmallSet<[u32; 2]> = vec![3, 1, 2].into_iter().collect();
+        let p: HashSet<u32> = s.clone().into();
+        assert!(p.len() == 3 && p.contains(&2));
+        assert!(s == p);
+        let back: SmallSet<[u32; 2]> = SmallSet::from(&p);
+        assert!(back == s);
+        let owned: SmallSet<[u32; 8]> = p.update(4).into();
+        assert!(owned.len() == 4 && owned.contains(&4));
+        assert!(s != p.update(4));
+    }
+}
@@ -0,0 +1,258 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A small insertion-ordered map.
+
+use smallvec::SmallVec;
+use std::fmt;
+use std::iter::FromIterator;
+use std::mem;
+use std::slice;
+
+/// A `SmallIndexMap` is the map counterpart of `SmallSet`: key-value pairs are
+/// stored as `(K, V)` tuples in a `SmallVec`, inline while there are no more
+/// than `N` of them, and found by linear scan. Entries are kept in
+/// insertion order and can also be addressed by their position, in the style
+/// of `indexmap::IndexMap`.
+///
+/// Any inline capacity `N` can be used.
+///
+/// ```
+/// use smallset::SmallIndexMap;
+///
+/// let mut m: SmallIndexMap<&str, u32, 4> = SmallIndexMap::new();
+/// m.insert("b", 1);
+/// m.insert("a", 2);
+/// assert!(m.get(&"a") == Some(&2));
+/// assert!(m.get_index(0) == Some((&"b", &1)));
+/// ```
+pub struct SmallIndexMap<K, V, const N: usize> {
+    entries: SmallVec<[(K, V); N]>,
+}
+
+impl<K: PartialEq + Eq, V, const N: usize> SmallIndexMap<K, V, N> {
+    /// Creates a new, empty `SmallIndexMap`.
+    #[inline]
+    pub fn new() -> SmallIndexMap<K, V, N> {
+        SmallIndexMap {
+            entries: SmallVec::new(),
+        }
+    }
+
+    /// Inserts a key-value pair. If `key` was already present, its value is
+    /// replaced (keeping the entry's position) and the old value is returned;
+    /// otherwise the entry is appended and `None` is returned.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.get_index_of(&key) {
+            Some(i) => Some(mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Returns the position of `key`, if present.
+    #[inline]
+    pub fn get_index_of(&self, key: &K) -> Option<usize> {
+        self.entries.iter().position(|e| e.0 == *key)
+    }
+
+    /// Returns the value for `key`, if present.
+    #[inline]
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.get_index_of(key).map(|i| &self.entries[i].1)
+    }
+
+    /// Returns a mutable reference to the value for `key`, if present.
+    #[inline]
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.get_index_of(key) {
+            Some(i) => Some(&mut self.entries[i].1),
+            None => None,
+        }
+    }
+
+    /// Returns the entry at position `index`, if there is one.
+    #[inline]
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        self.entries.get(index).map(|e| (&e.0, &e.1))
+    }
+
+    /// Tests whether `key` is present.
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get_index_of(key).is_some()
+    }
+
+    /// Removes `key` and returns its value, shifting all later entries down
+    /// by one so that the order of the remaining entries is preserved.
+    pub fn shift_remove(&mut self, key: &K) -> Option<V> {
+        self.get_index_of(key).map(|i| self.entries.remove(i).1)
+    }
+
+    /// Removes `key` and returns its value by swapping the last entry into
+    /// its position. This is faster than `shift_remove`, but perturbs the
+    /// order of the remaining entries.
+    pub fn swap_remove(&mut self, key: &K) -> Option<V> {
+        self.get_index_of(key)
+            .map(|i| self.entries.swap_remove(i).1)
+    }
+
+    /// Returns an iterator over the entries, in order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Returns an iterator over the keys, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Returns an iterator over the values, in order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Returns the number of entries.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Clears the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// An iterator over the entries of a `SmallIndexMap`, in order.
+pub struct Iter<'a, K: 'a, V: 'a> {
+    inner: slice::Iter<'a, (K, V)>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    #[inline]
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        self.inner.next().map(|e| (&e.0, &e.1))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    #[inline]
+    fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
+        self.inner.next_back().map(|e| (&e.0, &e.1))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<K, V, const N: usize> Default for SmallIndexMap<K, V, N>
+where
+    K: PartialEq + Eq,
+{
+    #[inline]
+    fn default() -> SmallIndexMap<K, V, N> {
+        SmallIndexMap::new()
+    }
+}
+
+impl<K, V, const N: usize> Clone for SmallIndexMap<K, V, N>
+where
+    K: Clone,
+    V: Clone,
+{
+    fn clone(&self) -> SmallIndexMap<K, V, N> {
+        SmallIndexMap {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<K, V, const N: usize> fmt::Debug for SmallIndexMap<K, V, N>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|e| (&e.0, &e.1)))
+            .finish()
+    }
+}
+
+impl<K, V, const N: usize> FromIterator<(K, V)> for SmallIndexMap<K, V, N>
+where
+    K: PartialEq + Eq,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = SmallIndexMap::new();
+        for (k, v) in iter {
+            map.insert(k, v);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_insert_get() {
+        let mut m: SmallIndexMap<u32, &str, 2> = SmallIndexMap::new();
+        assert!(m.insert(3, "c").is_none());
+        assert!(m.insert(1, "a").is_none());
+        assert!(m.insert(2, "b").is_none());
+        assert!(m.insert(1, "A") == Some("a"));
+        assert!(m.len() == 3);
+        assert!(m.get(&1) == Some(&"A"));
+        assert!(m.get(&4).is_none());
+        *m.get_mut(&2).unwrap() = "B";
+        assert!(m.iter().collect::<Vec<_>>() == vec![(&3, &"c"), (&1, &"A"), (&2, &"B")]);
+        assert!(m.get_index(1) == Some((&1, &"A")));
+        assert!(m.get_index(3).is_none());
+        assert!(m.get_index_of(&2) == Some(2));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut m: SmallIndexMap<u32, u32, 4> = (0..5).map(|i| (i, i * 10)).collect();
+        assert!(m.shift_remove(&1) == Some(10));
+        assert!(m.keys().copied().collect::<Vec<u32>>() == vec![0, 2, 3, 4]);
+        assert!(m.swap_remove(&0) == Some(0));
+        assert!(m.keys().copied().collect::<Vec<u32>>() == vec![4, 2, 3]);
+        assert!(m.swap_remove(&0).is_none());
+        assert!(m.values().copied().collect::<Vec<u32>>() == vec![40, 20, 30]);
+    }
+
+    #[test]
+    fn test_debug() {
+        let m: SmallIndexMap<u32, u32, 4> = vec![(2, 20), (1, 10)].into_iter().collect();
+        assert!(format!("{:?}", m) == "{2: 20, 1: 10}");
+    }
+}
@@ -0,0 +1,62 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Conversions to and from `indexmap::IndexSet`, enabled with the `indexmap`
+//! feature. Both types keep insertion order, and the conversions preserve it.
+
+use indexmap::IndexSet;
+use smallvec::Array;
+use std::hash::{BuildHasher, Hash};
+
+use SmallSet;
+
+impl<A: Array, S> From<SmallSet<A>> for IndexSet<A::Item, S>
+where
+    A::Item: PartialEq + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from(set: SmallSet<A>) -> IndexSet<A::Item, S> {
+        set.elements.into_iter().collect()
+    }
+}
+
+impl<A: Array, S> From<IndexSet<A::Item, S>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Hash,
+{
+    fn from(set: IndexSet<A::Item, S>) -> SmallSet<A> {
+        SmallSet {
+            elements: set.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a, A: Array, S> From<&'a IndexSet<A::Item, S>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Hash + Clone,
+{
+    fn from(set: &'a IndexSet<A::Item, S>) -> SmallSet<A> {
+        SmallSet {
+            elements: set.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_order_preserved() {
+        let s: SmallSet<[u32; 2]> = vec![3, 1, 2].into_iter().collect();
+        let i: IndexSet<u32> = s.clone().into();
+        assert!(i.iter().copied().collect::<Vec<u32>>() == vec![3, 1, 2]);
+        let back: SmallSet<[u32; 4]> = SmallSet::from(&i);
+        assert!(back.as_slice() == [3, 1, 2]);
+        let owned: SmallSet<[u32; 2]> = i.into();
+        assert!(owned == s);
+    }
+}
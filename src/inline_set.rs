@@ -0,0 +1,199 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A small set confined to its inline array.
+
+use smallvec::Array;
+use std::fmt;
+use std::slice::Iter;
+
+use errors::CapacityError;
+use SmallSet;
+
+/// An `InlineSmallSet` is a `SmallSet` that never leaves its inline array `A`.
+/// Inserting a new element into a full set fails with a `CapacityError` that
+/// hands the element back, rather than spilling to the heap. Callers that
+/// would rather treat a full set as a bug can `expect` the result.
+///
+/// The set only exposes operations that cannot grow its storage, so no method
+/// of this type reaches an allocation. The underlying `SmallVec` still has its
+/// heap variant, but an `InlineSmallSet` never selects it. Code that must not
+/// allocate can use this type in place of `SmallSet` and have the compiler
+/// reject any call that could spill. It is available with the `no-alloc`
+/// feature.
+///
+/// ```
+/// use smallset::InlineSmallSet;
+///
+/// let mut s: InlineSmallSet<[u32; 2]> = InlineSmallSet::new();
+/// assert!(s.insert(1) == Ok(true));
+/// assert!(s.insert(1) == Ok(false));
+/// assert!(s.insert(2) == Ok(true));
+/// assert!(s.insert(3).unwrap_err().into_element() == 3);
+/// assert!(s.as_slice() == [1, 2]);
+/// ```
+pub struct InlineSmallSet<A: Array>
+where
+    A::Item: PartialEq + Eq,
+{
+    set: SmallSet<A>,
+}
+
+impl<A: Array> InlineSmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    /// Creates a new, empty set.
+    #[inline]
+    pub fn new() -> InlineSmallSet<A> {
+        InlineSmallSet {
+            set: SmallSet::new(),
+        }
+    }
+
+    /// Inserts `elem` if not yet present. Returns `Ok(true)` if the element
+    /// was inserted and `Ok(false)` if it was already present. If the element
+    /// is new but the inline array is full, the set is left unchanged and
+    /// `elem` is handed back inside the error.
+    #[inline]
+    pub fn insert(&mut self, elem: A::Item) -> Result<bool, CapacityError<A::Item>> {
+        self.set
+            .insert_within_capacity(elem)
+            .map_err(CapacityError::new)
+    }
+
+    /// Removes `elem` from the set, preserving the order of the remaining
+    /// elements. Returns `true` if the element was removed.
+    #[inline]
+    pub fn remove(&mut self, elem: &A::Item) -> bool {
+        self.set.remove(elem)
+    }
+
+    /// Tests whether `elem` is present.
+    #[inline]
+    pub fn contains(&self, elem: &A::Item) -> bool {
+        self.set.contains(elem)
+    }
+
+    /// Keeps only the elements for which `pred` returns `true`, in order.
+    #[inline]
+    pub fn retain<F>(&mut self, pred: F)
+    where
+        F: FnMut(&A::Item) -> bool,
+    {
+        self.set.retain(pred)
+    }
+
+    /// Removes and returns the most recently inserted element, if any.
+    #[inline]
+    pub fn pop(&mut self) -> Option<A::Item> {
+        self.set.pop()
+    }
+
+    /// Removes and drops all elements.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.set.clear()
+    }
+
+    /// Returns the elements as a slice, in insertion order.
+    #[inline]
+    pub fn as_slice(&self) -> &[A::Item] {
+        self.set.as_slice()
+    }
+
+    /// Returns an iterator over the elements, in insertion order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, A::Item> {
+        self.set.iter()
+    }
+
+    /// Returns the number of elements in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.set.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.set.is_empty()
+    }
+
+    /// Returns the number of elements the set can hold, which is the inline
+    /// capacity of `A`.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.set.inline_size()
+    }
+
+    /// Returns `true` if no further elements can be inserted.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.set.is_inline_full()
+    }
+
+    /// Unwraps the set into an ordinary `SmallSet`, which is free to spill.
+    #[inline]
+    pub fn into_set(self) -> SmallSet<A> {
+        self.set
+    }
+}
+
+impl<A: Array> Default for InlineSmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn default() -> InlineSmallSet<A> {
+        InlineSmallSet::new()
+    }
+}
+
+impl<A: Array> fmt::Debug for InlineSmallSet<A>
+where
+    A::Item: PartialEq + Eq + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.set.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_full() {
+        let mut s: InlineSmallSet<[u32; 2]> = InlineSmallSet::new();
+        assert!(s.insert(1) == Ok(true) && s.insert(2) == Ok(true));
+        assert!(s.is_full() && s.capacity() == 2);
+        assert!(s.insert(2) == Ok(false));
+        assert!(s.insert(3) == Err(CapacityError::new(3)));
+        assert!(s.as_slice() == [1, 2]);
+        assert!(s.remove(&1) && s.insert(3) == Ok(true));
+        assert!(s.as_slice() == [2, 3]);
+        assert!(!s.into_set().spilled());
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut s: InlineSmallSet<[u32; 0]> = InlineSmallSet::new();
+        assert!(s.is_full());
+        assert!(s.insert(1).is_err() && s.is_empty());
+    }
+
+    #[test]
+    fn test_rejected_element_returned() {
+        let token = Rc::new(0);
+        let mut s: InlineSmallSet<[Rc<u32>; 1]> = InlineSmallSet::new();
+        s.insert(Rc::new(1)).unwrap();
+        let back = s.insert(token.clone()).unwrap_err().into_element();
+        assert!(Rc::ptr_eq(&back, &token) && Rc::strong_count(&token) == 2);
+        s.retain(|e| **e != 1);
+        assert!(s.is_empty());
+    }
+}
@@ -4,25 +4,180 @@
 // Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
 //
 
+#![cfg_attr(feature = "simd", feature(min_specialization, portable_simd))]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[cfg_attr(test, macro_use)]
+extern crate alloc;
+
+/// Without the `std` feature, this stands in for `std` with the parts of
+/// `core` and `alloc` that the crate uses, so `std::` paths work in both
+/// builds.
+#[cfg(not(feature = "std"))]
+mod std {
+    #[cfg(test)]
+    pub use alloc::rc;
+    pub use alloc::{borrow, boxed, collections, fmt, slice, str, string, vec};
+    pub use core::*;
+}
+
+use std::borrow::Borrow;
+use std::cmp;
+#[cfg(feature = "std")]
+use std::collections::hash_map::DefaultHasher;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+use std::convert::{TryFrom, TryInto};
 use std::fmt;
+use std::fmt::Write;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash, Hasher};
 use std::iter::{FromIterator, IntoIterator};
+use std::ops::{
+    BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Range, Sub, SubAssign,
+};
 use std::slice::Iter;
+use std::str::FromStr;
+use std::string::String;
+use std::vec::Vec;
 
 extern crate smallvec;
 use smallvec::{Array, SmallVec};
 
+#[cfg(feature = "arc-swap")]
+extern crate arc_swap;
+#[cfg(feature = "bincode")]
+extern crate bincode;
+#[cfg(feature = "clap")]
+extern crate clap;
+#[cfg(feature = "gc")]
+extern crate gc;
+#[cfg(feature = "im")]
+extern crate im;
+#[cfg(feature = "indexmap")]
+extern crate indexmap;
+#[cfg(feature = "rand")]
+extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde_with")]
+extern crate serde_with;
+#[cfg(feature = "speedy")]
+extern crate speedy;
+
+#[cfg(feature = "bincode")]
+mod bincode_impl;
+mod bitset;
+#[cfg(feature = "std")]
+mod bloom;
+mod borrowed;
+mod cell;
+#[cfg(feature = "clap")]
+mod clap_impl;
+mod demote;
+mod equivalence;
+#[macro_use]
+mod frozen;
+pub mod errors;
+#[cfg(feature = "gc")]
+mod gc_impl;
+mod guard;
+#[cfg(feature = "im")]
+mod im_impl;
+pub mod index_map;
+#[cfg(feature = "indexmap")]
+mod indexmap_impl;
+#[cfg(feature = "no-alloc")]
+mod inline_set;
+mod once;
+pub mod ord_set;
+mod pool;
+mod ptr;
+#[cfg(feature = "rand")]
+mod rand_impl;
+#[cfg(feature = "serde")]
+mod serde_impl;
+#[cfg(feature = "serde_with")]
+mod serde_with_impl;
+pub mod set_algebra;
+#[cfg(feature = "arc-swap")]
+mod shared;
+mod simd;
+#[cfg(feature = "speedy")]
+mod speedy_impl;
+pub use bitset::{BitElement, SmallBitSet};
+#[cfg(feature = "std")]
+pub use bloom::SmallBloom;
+pub use borrowed::SmallSetRef;
+pub use cell::SmallSetCell;
+#[cfg(feature = "clap")]
+pub use clap_impl::DuplicatePolicy;
+pub use demote::DemotingSmallSet;
+pub use equivalence::{AsciiCaseInsensitive, Equivalence, SmallSetBy};
+use errors::{
+    CapacityError, DuplicateError, NotSingleError, OccupiedError, ParseDelimitedError, ParseError,
+    RangeError,
+};
+pub use frozen::FrozenSmallSet;
+pub use guard::NoSpillGuard;
+pub use index_map::SmallIndexMap;
+#[cfg(feature = "no-alloc")]
+pub use inline_set::InlineSmallSet;
+pub use once::OnceSmallSet;
+pub use ord_set::SmallOrdSet;
+pub use pool::SpillPool;
+pub use ptr::PtrSmallSet;
+#[cfg(feature = "serde")]
+pub use serde_impl::serialize_sorted;
+#[cfg(feature = "arc-swap")]
+pub use shared::SharedSmallSet;
+use simd::SpecFind;
+
 /// A `SmallSet` is an unordered set of elements. It is designed to work best
 /// for very small sets (no more than ten or so elements). In order to support
 /// small sets very efficiently, it stores elements in a simple unordered array.
 /// When the set is smaller than the size of the array `A`, all elements are
 /// stored inline, without heap allocation. This is accomplished by using a
-/// `smallvec::SmallVec`.
+/// `smallvec::SmallVec`. An inline capacity of zero is allowed: a
+/// `SmallSet<[T; 0]>` keeps its elements on the heap from the first insert,
+/// for generic code that sometimes wants no inline storage.
+///
+/// By default, the `SmallVec` keeps a separate tag recording whether the
+/// elements are inline or on the heap. With the `union` feature, the tag is
+/// folded into the capacity word instead, which typically makes the set one
+/// word smaller. This needs Rust 1.49 or later.
 ///
 /// The insert, remove, and query methods on `SmallSet` have `O(n)` time
 /// complexity in the current set size: they perform a linear scan to determine
 /// if the element in question is present. This is inefficient for large sets,
 /// but fast and cache-friendly for small sets.
 ///
+/// Although the set is conceptually unordered, iteration order is guaranteed:
+/// elements are yielded in the order in which they were inserted, and
+/// `remove` preserves the relative order of the remaining elements. Operations
+/// that explicitly reorder the set say so in their documentation. Since the set
+/// never hashes its elements, iteration order depends only on the sequence of
+/// operations performed, and is reproducible from run to run whether or not
+/// the set has spilled to the heap. No feature or special hasher is needed for
+/// deterministic output.
+///
+/// When an insert spills an inline set to the heap, the heap buffer is sized
+/// to the next power of two above the new length, which is twice the inline
+/// capacity when that is a power of two. Later growth doubles the buffer, so a
+/// spilled set does not reallocate again until it has grown as much as it did
+/// inline. To choose the heap capacity up front instead, create the set with
+/// `with_capacity`, or call `reserve` or `reserve_exact` before inserting;
+/// these spill immediately if the requested capacity exceeds the inline array.
+///
+/// Methods that may need to allocate (`insert`, `collect`, and so on) panic or
+/// abort if the allocation fails. For contexts where that is unacceptable,
+/// `insert_within_capacity`, `fallible_insert`, `try_extend`, `try_reserve` and
+/// `try_reserve_exact` report failure as a value instead. The query methods,
+/// `remove` and `clear` never allocate. None of these methods panic unless the
+/// element type's `PartialEq` or `Drop` implementation does. With the
+/// `no-alloc` feature, `InlineSmallSet` offers a set that cannot spill at all.
+///
 /// Example usage:
 ///
 /// ```
@@ -36,40 +191,347 @@ use smallvec::{Array, SmallVec};
 /// assert!(s.len() == 3);
 /// assert!(s.contains(&1));
 /// ```
-pub struct SmallSet<A: Array>
-where
-    A::Item: PartialEq + Eq,
-{
+pub struct SmallSet<A: Array> {
     elements: SmallVec<A>,
 }
 
+/// A `SmallSet` of `T` holding up to `N` elements inline, for any `N`. This
+/// spells the same type as `SmallSet<[T; N]>` without the array type, which
+/// reads better in generic code.
+///
+/// ```
+/// use smallset::SmallSetN;
+///
+/// fn first_distinct<const N: usize>(v: &[u32]) -> SmallSetN<u32, N> {
+///     v.iter().copied().take(N).collect()
+/// }
+///
+/// let s = first_distinct::<3>(&[5, 6, 5, 7]);
+/// assert!(s.len() == 2);
+/// ```
+pub type SmallSetN<T, const N: usize> = SmallSet<[T; N]>;
+
+/// A `SmallSet` holding up to 4 elements inline.
+///
+/// ```
+/// use smallset::SmallSet4;
+///
+/// let mut s: SmallSet4<u32> = SmallSet4::new();
+/// s.insert(1);
+/// assert!(s.contains(&1));
+/// ```
+pub type SmallSet4<T> = SmallSet<[T; 4]>;
+
+/// A `SmallSet` holding up to 8 elements inline.
+pub type SmallSet8<T> = SmallSet<[T; 8]>;
+
+/// A `SmallSet` holding up to 16 elements inline.
+pub type SmallSet16<T> = SmallSet<[T; 16]>;
+
+/// Builds a `SmallSet` from a list of elements, like `vec!`. Duplicates are
+/// dropped, keeping the first occurrence. The inline size is inferred from
+/// context, or can be given with the form `smallset![T; N => elements]`.
+///
+/// ```
+/// #[macro_use]
+/// extern crate smallset;
+/// use smallset::SmallSet;
+///
+/// fn main() {
+///     let s: SmallSet<[u32; 4]> = smallset![1, 2, 3, 2];
+///     assert!(s.len() == 3);
+///     let t = smallset![u32; 2 => 3, 2, 1];
+///     assert!(s == t);
+///     let pairs = smallset![(u8, char); 4 => (1, 'a'), (2, 'b')];
+///     assert!(pairs.contains(&(2, 'b')));
+/// }
+/// ```
+#[macro_export]
+macro_rules! smallset {
+    // Collect tokens up to the first top-level `;` or `,` to tell the typed
+    // form from a plain list, without parsing elements as types or types as
+    // expressions.
+    (@scan [$($ty:tt)*] ; $n:expr => $($elem:expr),* $(,)*) => {
+        $crate::SmallSet::<[$($ty)*; $n]>::from([$($elem),*])
+    };
+    (@scan [$($head:tt)*] , $($rest:tt)*) => {
+        $crate::smallset!(@list $($head)*, $($rest)*)
+    };
+    (@scan [$($head:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::smallset!(@scan [$($head)* $next] $($rest)*)
+    };
+    (@scan [$($head:tt)*]) => {
+        $crate::smallset!(@list $($head)*)
+    };
+    (@list $($elem:expr),* $(,)*) => {
+        $crate::SmallSet::from([$($elem),*])
+    };
+    ($($tokens:tt)*) => {
+        $crate::smallset!(@scan [] $($tokens)*)
+    };
+}
+
+/// The outcome of `SmallSet::insert_detailed`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InsertOutcome {
+    /// An equal element was already present, so the set is unchanged.
+    AlreadyPresent,
+    /// The element was inserted, and the set stayed in its existing storage
+    /// (inline, or a heap buffer it already had).
+    Inserted,
+    /// The element was inserted, and this insert moved the set from inline
+    /// storage onto the heap.
+    InsertedAndSpilled,
+}
+
+impl InsertOutcome {
+    /// Returns `true` if the element was newly inserted.
+    #[inline]
+    pub fn is_new(self) -> bool {
+        self != InsertOutcome::AlreadyPresent
+    }
+
+    /// Returns `true` if this insert moved the set onto the heap.
+    #[inline]
+    pub fn spilled(self) -> bool {
+        self == InsertOutcome::InsertedAndSpilled
+    }
+}
+
 impl<A: Array> SmallSet<A>
 where
     A::Item: PartialEq + Eq,
 {
     /// Creates a new, empty `SmallSet`.
+    #[inline]
     pub fn new() -> SmallSet<A> {
         SmallSet {
             elements: SmallVec::new(),
         }
     }
 
+    /// Creates a new, empty `SmallSet` with room for at least `n` elements.
+    /// If `n` is larger than the inline array, the heap buffer is allocated
+    /// now, so bulk-loading the set does not spill partway through.
+    #[inline]
+    pub fn with_capacity(n: usize) -> SmallSet<A> {
+        SmallSet {
+            elements: SmallVec::with_capacity(n),
+        }
+    }
+
+    /// Creates a set holding every value in `range`. Since the values of a
+    /// range are distinct, this skips the membership checks that `collect`
+    /// would perform, and sizes the storage once from the range's length.
+    pub fn from_range(range: Range<A::Item>) -> SmallSet<A>
+    where
+        Range<A::Item>: Iterator<Item = A::Item>,
+    {
+        SmallSet {
+            elements: range.collect(),
+        }
+    }
+
     /// Inserts `elem` into the set if not yet present. Returns `true` if the
     /// set did not have this element present, or `false` if it already had this
     /// element present.
+    #[inline]
     pub fn insert(&mut self, elem: A::Item) -> bool {
         if !self.contains(&elem) {
-            self.elements.push(elem);
+            self.push_new(elem);
             true
         } else {
             false
         }
     }
 
+    /// Appends `elem`, which is known not to be present. Only the no-growth
+    /// path, a capacity check and a write, is inlined into callers. Growing
+    /// the storage, which spills an inline set or reallocates a spilled one,
+    /// is kept out of line in `push_grow`.
+    #[inline]
+    fn push_new(&mut self, elem: A::Item) {
+        let len = self.elements.len();
+        if len < self.elements.capacity() {
+            // SAFETY: `len < capacity`, so slot `len` is allocated and
+            // uninitialized, and becomes initialized before `set_len`.
+            unsafe {
+                self.elements.as_mut_ptr().add(len).write(elem);
+                self.elements.set_len(len + 1);
+            }
+        } else {
+            self.push_grow(elem);
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn push_grow(&mut self, elem: A::Item) {
+        self.elements.push(elem);
+    }
+
+    /// Converts `elem` into the element type and inserts it if not yet
+    /// present, like `insert`. This saves an explicit conversion at each call
+    /// site, e.g. inserting `&str`s into a set of `String`s. The duplicate
+    /// check is done on the converted value.
+    ///
+    /// ```
+    /// use smallset::SmallSet;
+    ///
+    /// let mut s: SmallSet<[String; 4]> = SmallSet::new();
+    /// assert!(s.insert_into("name"));
+    /// assert!(!s.insert_into("name"));
+    /// ```
+    #[inline]
+    pub fn insert_into<T: Into<A::Item>>(&mut self, elem: T) -> bool {
+        self.insert(elem.into())
+    }
+
+    /// Inserts `elem` into the set if not yet present, like `insert`, but also
+    /// reports whether this insert moved the set from inline storage onto the
+    /// heap. This lets callers log or count spills at the exact call site.
+    #[inline]
+    pub fn insert_detailed(&mut self, elem: A::Item) -> InsertOutcome {
+        if self.contains(&elem) {
+            return InsertOutcome::AlreadyPresent;
+        }
+        let was_spilled = self.elements.spilled();
+        self.push_new(elem);
+        if !was_spilled && self.elements.spilled() {
+            InsertOutcome::InsertedAndSpilled
+        } else {
+            InsertOutcome::Inserted
+        }
+    }
+
+    /// Inserts `elem` into the set, requiring that it was not yet present. If
+    /// an equal element is already in the set, the set is left unchanged and
+    /// `elem` is handed back inside the error.
+    pub fn try_insert(&mut self, elem: A::Item) -> Result<(), OccupiedError<A::Item>> {
+        if self.contains(&elem) {
+            Err(OccupiedError::new(elem))
+        } else {
+            self.push_new(elem);
+            Ok(())
+        }
+    }
+
+    /// Inserts `elem` if not yet present. If an equal element is already in
+    /// the set, calls `combiner` with the stored element and `elem` so that
+    /// the new value can be merged into it, rather than being discarded as
+    /// `insert` does. Returns `true` if `elem` was inserted as a new element.
+    ///
+    /// This is useful for elements whose equality only considers part of
+    /// their value, such as a key with some payload. The combiner must not
+    /// change the stored element in a way that makes it equal to another
+    /// element of the set.
+    pub fn insert_or_merge<F>(&mut self, elem: A::Item, combiner: F) -> bool
+    where
+        F: FnOnce(&mut A::Item, A::Item),
+    {
+        if let Some(existing) = self.elements.iter_mut().find(|e| **e == elem) {
+            combiner(existing, elem);
+            false
+        } else {
+            self.push_new(elem);
+            true
+        }
+    }
+
+    /// Inserts `elem` into the set if not yet present, without ever
+    /// allocating. Returns `Ok(true)` if the element was inserted and
+    /// `Ok(false)` if it was already present. If the element is new but there
+    /// is no spare room in the inline array or the existing heap buffer, the
+    /// set is left unchanged and `elem` is handed back as `Err(elem)`.
+    #[inline]
+    pub fn insert_within_capacity(&mut self, elem: A::Item) -> Result<bool, A::Item> {
+        if self.contains(&elem) {
+            Ok(false)
+        } else if self.elements.len() < self.elements.capacity() {
+            self.elements.push(elem);
+            Ok(true)
+        } else {
+            Err(elem)
+        }
+    }
+
+    /// Inserts `elem` into the set if not yet present, like `insert`, but
+    /// reports an allocation failure instead of panicking. On failure the set
+    /// is left unchanged and `elem` is handed back inside the error.
+    pub fn fallible_insert(&mut self, elem: A::Item) -> Result<bool, CapacityError<A::Item>> {
+        if self.contains(&elem) {
+            return Ok(false);
+        }
+        if self.elements.try_reserve(1).is_err() {
+            return Err(CapacityError::new(elem));
+        }
+        self.elements.push(elem);
+        Ok(true)
+    }
+
+    /// Inserts every element of `iter` that is not yet present, reporting an
+    /// allocation failure instead of panicking. On failure, the elements
+    /// yielded before the failing one remain inserted, the failing element is
+    /// handed back inside the error, and the rest of `iter` is not consumed.
+    pub fn try_extend<I>(&mut self, iter: I) -> Result<(), CapacityError<A::Item>>
+    where
+        I: IntoIterator<Item = A::Item>,
+    {
+        for elem in iter {
+            self.fallible_insert(elem)?;
+        }
+        Ok(())
+    }
+
+    /// Reserves room for `hint` more elements, the lower size bound of an
+    /// iterator about to be inserted. The bound counts duplicates, which the
+    /// set will not store, so the reservation is capped at four times the
+    /// larger of the inline size and the current length. An iterator of a
+    /// million copies of one element thus reserves a few slots, not a million;
+    /// past the cap, `insert` grows the buffer as usual.
+    #[inline]
+    fn reserve_for_hint(&mut self, hint: usize) {
+        // Zero-sized elements report an inline size of `usize::MAX`.
+        let cap = cmp::max(self.elements.inline_size(), self.len()).saturating_mul(4);
+        self.elements.reserve(cmp::min(hint, cap));
+    }
+
+    /// Replaces the contents of the set with the distinct elements of `iter`,
+    /// like `collect` but reusing the set's storage: an inline set stays
+    /// inline if the elements fit, and a spilled set keeps its heap buffer.
+    /// If `iter`'s lower size bound exceeds the current capacity, the set
+    /// grows up front rather than while inserting, within the limit described
+    /// at `reserve_for_hint`. Per-frame scratch sets can use this to avoid
+    /// reallocating.
+    pub fn collect_from<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = A::Item>,
+    {
+        self.elements.clear();
+        let iter = iter.into_iter();
+        self.reserve_for_hint(iter.size_hint().0);
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+
+    /// Inserts a clone of each element of `iter` that is not yet present, like
+    /// `extend` over references. `SmallSet` cannot implement
+    /// `Extend<&A::Item>` next to `Extend<A::Item>`, because coherence cannot
+    /// rule out the two item types being the same.
+    pub fn extend_cloned<'a, I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = &'a A::Item>,
+        A::Item: Clone + 'a,
+    {
+        self.extend(iter.into_iter().cloned());
+    }
+
     /// Removes `elem` from the set. Returns `true` if the element was removed,
     /// or `false` if it was not found.
+    #[inline]
     pub fn remove(&mut self, elem: &A::Item) -> bool {
-        if let Some(pos) = self.elements.iter().position(|e| *e == *elem) {
+        if let Some(pos) = SpecFind::position(&self.elements, elem) {
             self.elements.remove(pos);
             true
         } else {
@@ -77,126 +539,2243 @@ where
         }
     }
 
+    /// Inserts `elem` if it is not present, or removes it if it is. Returns
+    /// `true` if the element is present after the call. This performs a single
+    /// scan of the set.
+    #[inline]
+    pub fn toggle(&mut self, elem: A::Item) -> bool {
+        if let Some(pos) = SpecFind::position(&self.elements, &elem) {
+            self.elements.remove(pos);
+            false
+        } else {
+            self.push_new(elem);
+            true
+        }
+    }
+
     /// Tests whether `elem` is present. Returns `true` if it is present, or
     /// `false` if not.
+    #[inline]
     pub fn contains(&self, elem: &A::Item) -> bool {
-        self.elements.iter().any(|e| *e == *elem)
+        SpecFind::position(&self.elements, elem).is_some()
     }
 
-    /// Returns an iterator over the set elements. Elements will be returned in
-    /// an arbitrary (unsorted) order.
-    pub fn iter(&self) -> Iter<A::Item> {
-        self.elements.iter()
+    /// Tests each of `candidates` for membership in one call, returning a
+    /// `true`/`false` flag per candidate in the same order. The result is
+    /// stored inline when there are no more candidates than `B` holds.
+    pub fn contains_batch<B>(&self, candidates: &[A::Item]) -> SmallVec<B>
+    where
+        B: Array<Item = bool>,
+    {
+        let elems = self.elements.as_slice();
+        candidates.iter().map(|c| elems.contains(c)).collect()
     }
 
-    /// Returns the current length of the set.
-    pub fn len(&self) -> usize {
-        self.elements.len()
+    /// Keeps only the `k` elements with the largest keys, as computed by
+    /// `key_fn`, and removes the rest. The retained elements keep their
+    /// relative order. Among elements with equal keys, earlier ones are kept.
+    /// `key_fn` is called once per element.
+    pub fn retain_top_k_by<K, F>(&mut self, k: usize, mut key_fn: F)
+    where
+        K: Ord,
+        F: FnMut(&A::Item) -> K,
+    {
+        if k >= self.elements.len() {
+            return;
+        }
+        if k == 0 {
+            self.elements.clear();
+            return;
+        }
+        let mut keyed: SmallVec<[(K, usize); 8]> = self
+            .elements
+            .iter()
+            .enumerate()
+            .map(|(i, e)| (key_fn(e), i))
+            .collect();
+        keyed.select_nth_unstable_by(k - 1, |a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        let mut keep: SmallVec<[bool; 8]> = smallvec::smallvec![false; self.elements.len()];
+        for &(_, i) in &keyed[..k] {
+            keep[i] = true;
+        }
+        let mut i = 0;
+        self.elements.retain(|_| {
+            i += 1;
+            keep[i - 1]
+        });
     }
 
-    /// Clears the set.
-    pub fn clear(&mut self) {
-        self.elements.clear();
+    /// Keeps only the elements for which `pred` returns `true`. The retained
+    /// elements keep their relative order.
+    #[inline]
+    pub fn retain<F>(&mut self, mut pred: F)
+    where
+        F: FnMut(&A::Item) -> bool,
+    {
+        self.elements.retain(|e| pred(e));
     }
-}
 
-impl<A: Array> Clone for SmallSet<A>
-where
-    A::Item: PartialEq + Eq + Clone,
-{
-    fn clone(&self) -> SmallSet<A> {
-        SmallSet {
-            elements: self.elements.clone(),
+    /// Keeps only the elements for which `pred` returns `Ok(true)`, in order.
+    /// If `pred` returns an error, stops and returns it: elements already
+    /// rejected have been removed, and the failing element and all later ones
+    /// are kept. The retained elements keep their relative order.
+    pub fn try_retain<E, F>(&mut self, mut pred: F) -> Result<(), E>
+    where
+        F: FnMut(&A::Item) -> Result<bool, E>,
+    {
+        let mut i = 0;
+        while i < self.elements.len() {
+            if pred(&self.elements[i])? {
+                i += 1;
+            } else {
+                self.elements.remove(i);
+            }
         }
+        Ok(())
     }
-}
 
-impl<A: Array> fmt::Debug for SmallSet<A>
-where
-    A::Item: PartialEq + Eq + fmt::Debug,
-{
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.elements.fmt(f)
+    /// Returns an iterator that removes and yields, in order, the elements for
+    /// which `pred` returns `true`, leaving the rest in place in their
+    /// original order. `pred` is called on each element once as the iterator
+    /// advances; if the iterator is dropped early, the elements it has not
+    /// reached are kept.
+    ///
+    /// ```
+    /// use smallset::SmallSet;
+    ///
+    /// let mut s: SmallSet<[u32; 4]> = (0..6).collect();
+    /// let odd: Vec<u32> = s.extract_if(|e| e % 2 == 1).collect();
+    /// assert!(odd == vec![1, 3, 5]);
+    /// assert!(s.as_slice() == [0, 2, 4]);
+    /// ```
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, A, F>
+    where
+        F: FnMut(&A::Item) -> bool,
+    {
+        ExtractIf {
+            set: self,
+            index: 0,
+            pred,
+        }
     }
-}
 
-impl<A: Array> FromIterator<A::Item> for SmallSet<A>
-where
-    A::Item: PartialEq + Eq,
-{
-    fn from_iter<T>(iter: T) -> Self
+    /// Returns the element with the smallest key, as computed by `key_fn`, or
+    /// `None` if the set is empty. If several elements share the smallest
+    /// key, the first one is returned.
+    #[inline]
+    pub fn min_by_key<K, F>(&self, mut key_fn: F) -> Option<&A::Item>
     where
-        T: IntoIterator<Item = A::Item>,
+        K: Ord,
+        F: FnMut(&A::Item) -> K,
     {
-        SmallSet {
-            elements: SmallVec::from_iter(iter),
+        self.elements.iter().min_by_key(|e| key_fn(e))
+    }
+
+    /// Returns the element with the largest key, as computed by `key_fn`, or
+    /// `None` if the set is empty. If several elements share the largest key,
+    /// the last one is returned.
+    #[inline]
+    pub fn max_by_key<K, F>(&self, mut key_fn: F) -> Option<&A::Item>
+    where
+        K: Ord,
+        F: FnMut(&A::Item) -> K,
+    {
+        self.elements.iter().max_by_key(|e| key_fn(e))
+    }
+
+    /// Like `min_by_key`, but also returns the key of the chosen element.
+    /// `key_fn` is called exactly once per element.
+    pub fn arg_min_by_key<K, F>(&self, mut key_fn: F) -> Option<(&A::Item, K)>
+    where
+        K: Ord,
+        F: FnMut(&A::Item) -> K,
+    {
+        self.elements
+            .iter()
+            .map(|e| (e, key_fn(e)))
+            .min_by(|a, b| a.1.cmp(&b.1))
+    }
+
+    /// Like `max_by_key`, but also returns the key of the chosen element.
+    /// `key_fn` is called exactly once per element.
+    pub fn arg_max_by_key<K, F>(&self, mut key_fn: F) -> Option<(&A::Item, K)>
+    where
+        K: Ord,
+        F: FnMut(&A::Item) -> K,
+    {
+        self.elements
+            .iter()
+            .map(|e| (e, key_fn(e)))
+            .max_by(|a, b| a.1.cmp(&b.1))
+    }
+
+    /// Returns `true` if `self` and `other` have at least `k` elements in
+    /// common. The scan stops as soon as the `k`th common element is found,
+    /// or as soon as too few elements remain for `k` to be reached.
+    pub fn shares_at_least<B>(&self, other: &SmallSet<B>, k: usize) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        if k == 0 {
+            return true;
+        }
+        if k > other.len() {
+            return false;
         }
+        let mut found = 0;
+        let len = self.len();
+        for (i, elem) in self.iter().enumerate() {
+            if found + (len - i) < k {
+                return false;
+            }
+            if other.contains(elem) {
+                found += 1;
+                if found == k {
+                    return true;
+                }
+            }
+        }
+        false
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use std::fmt::Write;
+    /// Returns `true` if every element of `self` is in `other`. Fails fast
+    /// when `self` is the larger set.
+    pub fn is_subset<B>(&self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        self.len() <= other.len() && self.iter().all(|e| other.contains(e))
+    }
 
-    #[test]
-    fn test_basic_set() {
-        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
-        assert!(s.insert(1) == true);
-        assert!(s.insert(2) == true);
-        assert!(s.insert(2) == false);
-        assert!(s.insert(3) == true);
-        assert!(s.insert(2) == false);
-        assert!(s.insert(3) == false);
-        assert!(s.contains(&1));
-        assert!(s.contains(&2));
-        assert!(s.contains(&3));
-        assert!(!s.contains(&4));
-        assert!(s.len() == 3);
-        assert!(s.iter().map(|r| *r).collect::<Vec<u32>>() == vec![1, 2, 3]);
-        s.clear();
-        assert!(!s.contains(&1));
+    /// Returns `true` if every element of `other` is in `self`.
+    #[inline]
+    pub fn is_superset<B>(&self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        other.is_subset(self)
     }
 
-    #[test]
-    fn test_remove() {
-        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
-        assert!(s.insert(1) == true);
-        assert!(s.insert(2) == true);
-        assert!(s.len() == 2);
-        assert!(s.contains(&1));
-        assert!(s.remove(&1) == true);
-        assert!(s.remove(&1) == false);
-        assert!(s.len() == 1);
-        assert!(!s.contains(&1));
-        assert!(s.insert(1) == true);
-        assert!(s.iter().map(|r| *r).collect::<Vec<u32>>() == vec![2, 1]);
+    /// Returns `true` if `self` is a subset of `other` and the two differ.
+    pub fn is_proper_subset<B>(&self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        self.len() < other.len() && self.is_subset(other)
     }
 
-    #[test]
-    fn test_clone() {
-        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
-        s.insert(1);
-        s.insert(2);
-        let c = s.clone();
-        assert!(c.contains(&1));
-        assert!(c.contains(&2));
-        assert!(!c.contains(&3));
+    /// Returns `true` if `self` is a superset of `other` and the two differ.
+    #[inline]
+    pub fn is_proper_superset<B>(&self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        other.is_proper_subset(self)
     }
 
-    #[test]
-    fn test_debug() {
-        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
-        s.insert(1);
-        s.insert(2);
-        let mut buf = String::new();
-        write!(buf, "{:?}", s).unwrap();
-        assert!(&buf == "[1, 2]");
+    /// Returns `true` if `self` and `other` have no elements in common.
+    #[inline]
+    pub fn is_disjoint<B>(&self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        !self.shares_at_least(other, 1)
     }
 
-    #[test]
-    fn test_fromiter() {
-        let s: SmallSet<[usize; 4]> = vec![1, 2, 3, 4].into_iter().collect();
-        assert!(s.len() == 4);
+    /// Returns the union of `self` and `other`, which are required to be
+    /// disjoint: the elements of `self` in order, followed by those of
+    /// `other`. If the sets overlap, fails with the first element of `other`
+    /// that is also in `self`. This is meant for merging sets that should
+    /// partition some collection, where an overlap indicates a bug.
+    pub fn disjoint_union<B>(
+        mut self,
+        other: SmallSet<B>,
+    ) -> Result<SmallSet<A>, DuplicateError<A::Item>>
+    where
+        B: Array<Item = A::Item>,
+    {
+        let len = self.len();
+        self.elements.reserve(other.len());
+        for elem in other.elements {
+            if self.elements[..len].contains(&elem) {
+                return Err(DuplicateError::new(elem));
+            }
+            self.elements.push(elem);
+        }
+        Ok(self)
+    }
+
+    /// Returns the only element of the set, or an error reporting how many
+    /// elements there were if the set does not hold exactly one.
+    #[inline]
+    pub fn exactly_one(&self) -> Result<&A::Item, NotSingleError> {
+        match self.elements.as_slice() {
+            [elem] => Ok(elem),
+            elems => Err(NotSingleError::new(elems.len(), ())),
+        }
+    }
+
+    /// Consumes the set and returns its only element. If the set does not
+    /// hold exactly one element, it is handed back inside the error.
+    pub fn try_into_single(mut self) -> Result<A::Item, NotSingleError<SmallSet<A>>> {
+        if self.len() == 1 {
+            Ok(self.elements.pop().unwrap())
+        } else {
+            Err(NotSingleError::new(self.len(), self))
+        }
+    }
+
+    /// Returns the stored element equal to `key`, if any. `key` may be any
+    /// borrowed form of the element type, such as `&str` for a set of
+    /// `String`s. This is a linear scan whether or not the set has spilled.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&A::Item>
+    where
+        A::Item: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.elements.iter().find(|e| (*e).borrow() == key)
+    }
+
+    /// Looks up several keys at once, returning the stored element equal to
+    /// each key, if any, in the same order as `keys`. The set is scanned once
+    /// for all keys, stopping early once every key has been found.
+    pub fn get_many<Q, const K: usize>(&self, keys: [&Q; K]) -> [Option<&A::Item>; K]
+    where
+        A::Item: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let mut found = [None; K];
+        let mut remaining = K;
+        for elem in self.elements.iter() {
+            if remaining == 0 {
+                break;
+            }
+            for (slot, key) in found.iter_mut().zip(keys.iter()) {
+                if slot.is_none() && elem.borrow() == *key {
+                    *slot = Some(elem);
+                    remaining -= 1;
+                }
+            }
+        }
+        found
+    }
+
+    /// Removes every element that is not in `other`, keeping the order of the
+    /// rest. Returns `true` if any element was removed.
+    pub fn intersection_with<B>(&mut self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        let len = self.len();
+        self.elements.retain(|e| other.contains(e));
+        self.len() != len
+    }
+
+    /// Removes every element that is in `other`, keeping the order of the
+    /// rest. Returns `true` if any element was removed.
+    pub fn difference_with<B>(&mut self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        let len = self.len();
+        self.elements.retain(|e| !other.contains(e));
+        self.len() != len
+    }
+
+    /// Moves the elements of `other` that are not yet present into this set,
+    /// appending them in `other`'s order. Returns `true` if any element was
+    /// added.
+    pub fn extend_from_set<B>(&mut self, other: SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        let len = self.len();
+        for elem in other.elements {
+            self.insert(elem);
+        }
+        self.len() != len
+    }
+
+    /// Returns the complement of this set within `universe`: a new set of the
+    /// elements yielded by `universe` that are not in `self`. Elements of
+    /// `self` that do not appear in `universe` are ignored.
+    pub fn complement<I>(&self, universe: I) -> SmallSet<A>
+    where
+        I: IntoIterator<Item = A::Item>,
+    {
+        universe.into_iter().filter(|e| !self.contains(e)).collect()
+    }
+
+    /// Returns some element of the set, or `None` if the set is empty. This
+    /// is the earliest-inserted element still present, and is cheaper than
+    /// constructing an iterator just to take its first item.
+    #[inline]
+    pub fn any_element(&self) -> Option<&A::Item> {
+        self.elements.first()
+    }
+
+    /// Removes and returns some element of the set, or `None` if the set is
+    /// empty. This is the most recently inserted element still present, so a
+    /// set used as a worklist is processed last-in first-out. Runs in
+    /// constant time and never allocates.
+    #[inline]
+    pub fn pop(&mut self) -> Option<A::Item> {
+        self.elements.pop()
+    }
+
+    /// Returns an iterator over the set elements. Elements are returned in
+    /// insertion order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, A::Item> {
+        self.elements.iter()
+    }
+
+    /// Returns the set elements as a slice, in iteration order.
+    #[inline]
+    pub fn as_slice(&self) -> &[A::Item] {
+        self.elements.as_slice()
+    }
+
+    /// Returns the set elements as a mutable slice, in iteration order, so
+    /// that they can be sorted or modified in place by slice-based code. This
+    /// works whether the set is inline or has spilled.
+    ///
+    /// # Safety
+    ///
+    /// The set relies on its elements being pairwise distinct. When the
+    /// returned slice is released, no two elements may compare equal.
+    /// Reordering elements is always fine. Violating this does not cause
+    /// memory unsafety within this crate, but later operations may behave
+    /// incorrectly, e.g. `remove` may leave an equal element behind.
+    #[inline]
+    pub unsafe fn as_mut_slice(&mut self) -> &mut [A::Item] {
+        self.elements.as_mut_slice()
+    }
+
+    /// Returns the current length of the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Returns the number of elements the set can hold without reallocating:
+    /// the inline size while inline, and the heap buffer's size once spilled.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.elements.capacity()
+    }
+
+    /// Returns the number of elements the inline array holds.
+    #[inline]
+    pub fn inline_size(&self) -> usize {
+        self.elements.inline_size()
+    }
+
+    /// Returns `true` if the set has spilled to the heap.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        self.elements.spilled()
+    }
+
+    /// Returns `true` if the set is still stored inline and every inline slot
+    /// is in use.
+    #[inline]
+    pub fn is_inline_full(&self) -> bool {
+        !self.elements.spilled() && self.elements.len() == self.elements.inline_size()
+    }
+
+    /// Returns `true` if inserting an element not yet present would move the
+    /// set from inline storage to the heap. Latency-sensitive callers can use
+    /// this to evict an element, reject the insert, or spill ahead of time
+    /// (e.g. with `try_reserve`) outside of a critical section.
+    #[inline]
+    pub fn will_spill_on_insert(&self) -> bool {
+        self.is_inline_full()
+    }
+
+    /// Clears the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    /// Reserves capacity for at least `additional` more elements. May reserve
+    /// more space to avoid frequent reallocations. Panics if the new capacity
+    /// overflows `usize`.
+    pub fn reserve(&mut self, additional: usize) {
+        self.elements.reserve(additional);
+    }
+
+    /// Reserves capacity for exactly `additional` more elements, without the
+    /// over-allocation `reserve` may perform. Useful when the final size is
+    /// known and memory is tight. If the result fits inline, nothing is
+    /// allocated. Panics if the new capacity overflows `usize`.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        self.elements.reserve_exact(additional);
+    }
+
+    /// Reserves capacity for at least `additional` more elements, reporting
+    /// an allocation failure instead of panicking. May reserve more space to
+    /// avoid frequent reallocations. Once this succeeds, the next
+    /// `additional` insertions are guaranteed not to allocate.
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), CapacityError> {
+        self.elements
+            .try_reserve(additional)
+            .map_err(|_| CapacityError::new(()))
+    }
+
+    /// Reserves capacity for exactly `additional` more elements, reporting an
+    /// allocation failure instead of panicking.
+    pub fn try_reserve_exact(&mut self, additional: usize) -> Result<(), CapacityError> {
+        self.elements
+            .try_reserve_exact(additional)
+            .map_err(|_| CapacityError::new(()))
+    }
+
+    /// Moves the set onto the heap now, if it is still inline, with room for
+    /// the same number of elements it would get by spilling on insert.
+    /// Callers about to insert many elements can use this to pay for the
+    /// spill up front, outside of a hot loop.
+    pub fn force_heap(&mut self) {
+        if !self.elements.spilled() {
+            let cap = cmp::max(self.elements.inline_size() + 1, self.len()).next_power_of_two();
+            self.elements.grow(cap);
+        }
+    }
+
+    /// Moves the set back into its inline array, freeing the heap buffer.
+    /// Fails, leaving the set unchanged, if it has more elements than the
+    /// inline array holds.
+    pub fn force_inline(&mut self) -> Result<(), CapacityError> {
+        if self.len() > self.elements.inline_size() {
+            return Err(CapacityError::new(()));
+        }
+        self.elements.shrink_to_fit();
+        Ok(())
+    }
+
+    /// Shrinks the capacity of the set as much as possible. If the elements
+    /// fit in the inline array, they are moved back inline and the heap
+    /// allocation is freed; otherwise the heap buffer is shrunk to the
+    /// current length.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.elements.shrink_to_fit();
+    }
+
+    /// Shrinks the capacity of the set so that it can hold at least
+    /// `min_capacity` elements, or the current length if that is larger. If
+    /// the resulting capacity fits within the inline array, the elements are
+    /// moved back inline and the heap allocation is freed. Does nothing if the
+    /// capacity is already smaller.
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let target = cmp::max(self.len(), min_capacity);
+        if self.elements.capacity() > target {
+            self.elements.grow(target);
+        }
+    }
+
+    /// Converts the set into an array of exactly `N` distinct elements, in
+    /// iteration order. If the set does not contain exactly `N` elements, it
+    /// is handed back unchanged as the error.
+    pub fn into_array<const N: usize>(self) -> Result<[A::Item; N], SmallSet<A>> {
+        if self.len() != N {
+            return Err(self);
+        }
+        let mut elems = self.elements.into_iter();
+        Ok(std::array::from_fn(|_| elems.next().unwrap()))
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Clone,
+{
+    /// Adds the elements of `other` that are not yet present, appending them
+    /// in `other`'s order. Returns `true` if any element was added, which
+    /// makes this convenient for fixpoint iteration.
+    pub fn union_with<B>(&mut self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        let len = self.len();
+        for elem in other.iter() {
+            if !self.contains(elem) {
+                self.elements.push(elem.clone());
+            }
+        }
+        self.len() != len
+    }
+
+    /// Replaces the set with the symmetric difference of itself and `other`:
+    /// elements in both are removed, keeping the order of the rest, and the
+    /// elements of `other` not in `self` are appended in `other`'s order.
+    /// Returns `true` if the set changed, which is whenever `other` is
+    /// non-empty.
+    pub fn symmetric_difference_with<B>(&mut self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        let len = self.len();
+        for elem in other.iter() {
+            if !self.elements[..len].contains(elem) {
+                self.elements.push(elem.clone());
+            }
+        }
+        let mut i = 0;
+        self.elements.retain(|e| {
+            i += 1;
+            i > len || !other.contains(e)
+        });
+        !other.is_empty()
+    }
+
+    /// Clears `out` and fills it with the union of `self` and `other`: the
+    /// elements of `self` in order, followed by the elements of `other` that
+    /// are not in `self`. Any heap buffer already owned by `out` is reused, so
+    /// computing many unions into the same scratch set does not allocate once
+    /// it has grown large enough.
+    pub fn union_into<B, C>(&self, other: &SmallSet<B>, out: &mut SmallSet<C>)
+    where
+        B: Array<Item = A::Item>,
+        C: Array<Item = A::Item>,
+    {
+        out.clear();
+        out.elements.extend(self.iter().cloned());
+        out.elements
+            .extend(other.iter().filter(|e| !self.contains(e)).cloned());
+    }
+
+    /// Clears `out` and fills it with the elements of `self` that are also in
+    /// `other`, in the order of `self`. Reuses the heap buffer of `out` like
+    /// `union_into`.
+    pub fn intersection_into<B, C>(&self, other: &SmallSet<B>, out: &mut SmallSet<C>)
+    where
+        B: Array<Item = A::Item>,
+        C: Array<Item = A::Item>,
+    {
+        out.clear();
+        out.elements
+            .extend(self.iter().filter(|e| other.contains(e)).cloned());
+    }
+
+    /// Clears `out` and fills it with the elements of `self` that are not in
+    /// `other`, in the order of `self`. Reuses the heap buffer of `out` like
+    /// `union_into`.
+    pub fn difference_into<B, C>(&self, other: &SmallSet<B>, out: &mut SmallSet<C>)
+    where
+        B: Array<Item = A::Item>,
+        C: Array<Item = A::Item>,
+    {
+        out.clear();
+        out.elements
+            .extend(self.iter().filter(|e| !other.contains(e)).cloned());
+    }
+}
+
+/// Implements a set operator for `&SmallSet` and its `*Assign` form for
+/// `SmallSet`, mirroring `std::collections::HashSet`, in terms of one of the
+/// in-place set operations.
+macro_rules! set_operator {
+    ($op:ident, $op_fn:ident, $assign:ident, $assign_fn:ident, $in_place:ident) => {
+        impl<'a, 'b, A: Array, B: Array<Item = A::Item>> $op<&'b SmallSet<B>> for &'a SmallSet<A>
+        where
+            A::Item: PartialEq + Eq + Clone,
+        {
+            type Output = SmallSet<A>;
+
+            fn $op_fn(self, other: &'b SmallSet<B>) -> SmallSet<A> {
+                let mut out = self.clone();
+                out.$in_place(other);
+                out
+            }
+        }
+
+        impl<'b, A: Array, B: Array<Item = A::Item>> $assign<&'b SmallSet<B>> for SmallSet<A>
+        where
+            A::Item: PartialEq + Eq + Clone,
+        {
+            fn $assign_fn(&mut self, other: &'b SmallSet<B>) {
+                self.$in_place(other);
+            }
+        }
+    };
+}
+
+set_operator!(BitOr, bitor, BitOrAssign, bitor_assign, union_with);
+set_operator!(
+    BitAnd,
+    bitand,
+    BitAndAssign,
+    bitand_assign,
+    intersection_with
+);
+set_operator!(Sub, sub, SubAssign, sub_assign, difference_with);
+set_operator!(
+    BitXor,
+    bitxor,
+    BitXorAssign,
+    bitxor_assign,
+    symmetric_difference_with
+);
+
+/// Equivalent to `SmallSet::new`: an empty, inline set.
+impl<A: Array> Default for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    #[inline]
+    fn default() -> SmallSet<A> {
+        SmallSet::new()
+    }
+}
+
+impl<A: Array> Clone for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Clone,
+{
+    fn clone(&self) -> SmallSet<A> {
+        SmallSet {
+            elements: self.elements.clone(),
+        }
+    }
+}
+
+/// Two sets are equal if they contain the same elements, regardless of order
+/// or inline capacity. Elements in a common prefix are compared pairwise, so
+/// sets built by the same sequence of operations compare in linear time; the
+/// remaining elements are compared by a quadratic scan, since only `Eq` is
+/// available here. For large sets of `Ord` elements, `eq_by_sorting` makes
+/// the worst case `O(n log n)`.
+impl<A: Array, B: Array<Item = A::Item>> PartialEq<SmallSet<B>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn eq(&self, other: &SmallSet<B>) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        let (a, b) = (self.elements.as_slice(), other.elements.as_slice());
+        let common = a.iter().zip(b).take_while(|&(x, y)| x == y).count();
+        let b = &b[common..];
+        a[common..].iter().all(|e| b.contains(e))
+    }
+}
+
+impl<A: Array> Eq for SmallSet<A> where A::Item: PartialEq + Eq {}
+
+/// A set equals a slice if every element of each is in the other, regardless
+/// of order. Duplicates in the slice are ignored.
+impl<A: Array> PartialEq<[A::Item]> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn eq(&self, other: &[A::Item]) -> bool {
+        self.len() <= other.len()
+            && other.iter().all(|e| self.contains(e))
+            && self.iter().all(|e| other.contains(e))
+    }
+}
+
+impl<A: Array, const N: usize> PartialEq<[A::Item; N]> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    #[inline]
+    fn eq(&self, other: &[A::Item; N]) -> bool {
+        *self == other[..]
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: Array, S: BuildHasher> PartialEq<HashSet<A::Item, S>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Hash,
+{
+    fn eq(&self, other: &HashSet<A::Item, S>) -> bool {
+        self.len() == other.len() && self.iter().all(|e| other.contains(e))
+    }
+}
+
+/// Hashes the set independently of element order, consistently with
+/// `PartialEq`: each element is hashed on its own with a fixed-key hasher,
+/// and the results are summed. This makes sets usable as `HashMap` keys.
+#[cfg(feature = "std")]
+impl<A: Array> Hash for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut sum: u64 = 0;
+        for elem in self.iter() {
+            let mut h = DefaultHasher::new();
+            elem.hash(&mut h);
+            sum = sum.wrapping_add(h.finish());
+        }
+        state.write_usize(self.len());
+        state.write_u64(sum);
+    }
+}
+
+impl<A: Array> fmt::Debug for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.elements.fmt(f)
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + fmt::Debug,
+{
+    /// Returns a wrapper whose `Debug` output shows at most `max` elements,
+    /// followed by a count of the elements left out, e.g. `[1, 2, … (+998
+    /// more)]`. This keeps log lines bounded for sets that have grown large;
+    /// the set's own `Debug` impl still prints every element.
+    #[inline]
+    pub fn debug_truncated(&self, max: usize) -> DebugTruncated<'_, A> {
+        DebugTruncated { set: self, max }
+    }
+}
+
+/// A `Debug` wrapper that prints a bounded prefix of a set. Returned by
+/// `SmallSet::debug_truncated`.
+pub struct DebugTruncated<'a, A: Array + 'a> {
+    set: &'a SmallSet<A>,
+    max: usize,
+}
+
+impl<'a, A: Array> fmt::Debug for DebugTruncated<'a, A>
+where
+    A::Item: PartialEq + Eq + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        struct More(usize);
+        impl fmt::Debug for More {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "\u{2026} (+{} more)", self.0)
+            }
+        }
+        let mut list = f.debug_list();
+        list.entries(self.set.iter().take(self.max));
+        if self.set.len() > self.max {
+            list.entry(&More(self.set.len() - self.max));
+        }
+        list.finish()
+    }
+}
+
+impl<A: Array> IntoIterator for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    type Item = A::Item;
+    type IntoIter = IntoIter<A>;
+
+    #[inline]
+    fn into_iter(self) -> IntoIter<A> {
+        IntoIter {
+            inner: self.elements.into_iter(),
+        }
+    }
+}
+
+/// An iterator that moves the elements out of a `SmallSet`, in iteration
+/// order. Elements not yet yielded are dropped with the iterator.
+pub struct IntoIter<A: Array> {
+    inner: smallvec::IntoIter<A>,
+}
+
+impl<A: Array> IntoIter<A> {
+    /// Returns the elements not yet yielded, as a slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[A::Item] {
+        self.inner.as_slice()
+    }
+}
+
+impl<A: Array> Iterator for IntoIter<A> {
+    type Item = A::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<A::Item> {
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<A: Array> DoubleEndedIterator for IntoIter<A> {
+    #[inline]
+    fn next_back(&mut self) -> Option<A::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<A: Array> ExactSizeIterator for IntoIter<A> {}
+
+/// An iterator that removes the elements of a `SmallSet` matching a
+/// predicate. Returned by `SmallSet::extract_if`.
+pub struct ExtractIf<'a, A: Array + 'a, F> {
+    set: &'a mut SmallSet<A>,
+    index: usize,
+    pred: F,
+}
+
+impl<'a, A: Array, F> Iterator for ExtractIf<'a, A, F>
+where
+    F: FnMut(&A::Item) -> bool,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        while self.index < self.set.elements.len() {
+            if (self.pred)(&self.set.elements[self.index]) {
+                return Some(self.set.elements.remove(self.index));
+            }
+            self.index += 1;
+        }
+        None
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.set.elements.len() - self.index))
+    }
+}
+
+impl<'a, A: Array> IntoIterator for &'a SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    type Item = &'a A::Item;
+    type IntoIter = Iter<'a, A::Item>;
+
+    #[inline]
+    fn into_iter(self) -> Iter<'a, A::Item> {
+        self.iter()
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + FromStr,
+{
+    /// Parses a set from a string of items separated by `sep`, such as
+    /// `"a,b,c"` in a configuration value or environment variable. Whitespace
+    /// around each item is trimmed, and an empty or all-whitespace string
+    /// gives an empty set. Fails if any item does not parse, or if an item
+    /// appears more than once.
+    ///
+    /// ```
+    /// use smallset::SmallSet;
+    ///
+    /// let s: SmallSet<[u16; 4]> = SmallSet::parse_delimited("80, 443", ',').unwrap();
+    /// assert!(s.format_delimited(",") == "80,443");
+    /// assert!(SmallSet::<[u16; 4]>::parse_delimited("80,80", ',').is_err());
+    /// ```
+    pub fn parse_delimited(
+        s: &str,
+        sep: char,
+    ) -> Result<SmallSet<A>, ParseDelimitedError<A::Item>> {
+        let mut set = SmallSet::new();
+        if s.trim().is_empty() {
+            return Ok(set);
+        }
+        for item in s.split(sep) {
+            let elem = item.trim().parse().map_err(ParseError::Invalid)?;
+            if let Err(e) = set.try_insert(elem) {
+                return Err(ParseError::Duplicate(DuplicateError::new(e.into_element())));
+            }
+        }
+        Ok(set)
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + fmt::Display,
+{
+    /// Formats the set as its items in iteration order, separated by `sep`.
+    /// This is the inverse of `parse_delimited` for items whose `Display` and
+    /// `FromStr` implementations round-trip and never contain `sep`.
+    pub fn format_delimited(&self, sep: &str) -> String {
+        let mut out = String::new();
+        for (i, elem) in self.iter().enumerate() {
+            if i > 0 {
+                out.push_str(sep);
+            }
+            write!(out, "{}", elem).unwrap();
+        }
+        out
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Clone + TryInto<u8>,
+{
+    /// Returns a bitmask with bit `i` set for each element `i` of a set of
+    /// small integers. Fails with the first element, in iteration order, that
+    /// is negative or at least 64.
+    pub fn to_bitmask_u64(&self) -> Result<u64, RangeError<A::Item>> {
+        self.to_bitmask(64).map(|m| m as u64)
+    }
+
+    /// Like `to_bitmask_u64`, but for elements below 128.
+    pub fn to_bitmask_u128(&self) -> Result<u128, RangeError<A::Item>> {
+        self.to_bitmask(128)
+    }
+
+    fn to_bitmask(&self, bits: u8) -> Result<u128, RangeError<A::Item>> {
+        let mut mask = 0u128;
+        for elem in self.iter() {
+            match elem.clone().try_into() {
+                Ok(i) if i < bits => mask |= 1 << i,
+                _ => return Err(RangeError::new(elem.clone())),
+            }
+        }
+        Ok(mask)
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + From<u8>,
+{
+    /// Creates a set of small integers containing `i` for each bit `i` set in
+    /// `mask`, in ascending order.
+    pub fn from_bitmask_u64(mask: u64) -> SmallSet<A> {
+        SmallSet::from_bitmask_u128(mask as u128)
+    }
+
+    /// Like `from_bitmask_u64`, for a 128-bit mask.
+    pub fn from_bitmask_u128(mut mask: u128) -> SmallSet<A> {
+        let mut set = SmallSet::new();
+        while mask != 0 {
+            set.insert(A::Item::from(mask.trailing_zeros() as u8));
+            mask &= mask - 1;
+        }
+        set
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Ord,
+{
+    /// Sorts the elements in place into ascending order, so that iteration,
+    /// `as_slice` and `Debug` output are canonical. This reorders the set,
+    /// and works the same whether the set is inline or has spilled. It does
+    /// not allocate.
+    #[inline]
+    pub fn sort(&mut self) {
+        self.elements.sort_unstable();
+    }
+
+    /// Removes all elements from the set and returns them in ascending order.
+    /// The elements are sorted in place, so this does not allocate. If the
+    /// iterator is dropped before it is exhausted, the remaining elements are
+    /// dropped too, and the set is left empty either way.
+    pub fn drain_sorted(&mut self) -> impl Iterator<Item = A::Item> + '_ {
+        self.elements.sort_unstable();
+        self.elements.drain(..)
+    }
+
+    /// Tests whether `self` and `other` contain the same elements, like `==`,
+    /// but in `O(n log n)` rather than `O(n * m)` time in the worst case.
+    /// After the common prefix, the remaining elements of each set are
+    /// sorted by reference and compared pairwise. Up to 16 remaining
+    /// elements are sorted without allocating.
+    pub fn eq_by_sorting<B>(&self, other: &SmallSet<B>) -> bool
+    where
+        B: Array<Item = A::Item>,
+    {
+        if self.len() != other.len() {
+            return false;
+        }
+        let (a, b) = (self.elements.as_slice(), other.elements.as_slice());
+        let common = a.iter().zip(b).take_while(|&(x, y)| x == y).count();
+        let mut a: SmallVec<[&A::Item; 16]> = a[common..].iter().collect();
+        let mut b: SmallVec<[&A::Item; 16]> = b[common..].iter().collect();
+        a.sort_unstable();
+        b.sort_unstable();
+        a == b
+    }
+}
+
+/// Equivalent to `SmallSet::into_array`.
+impl<A: Array, const N: usize> TryFrom<SmallSet<A>> for [A::Item; N]
+where
+    A::Item: PartialEq + Eq,
+{
+    type Error = SmallSet<A>;
+
+    fn try_from(set: SmallSet<A>) -> Result<[A::Item; N], SmallSet<A>> {
+        set.into_array()
+    }
+}
+
+/// Builds a set from a `SmallVec`, keeping its storage. Duplicates are
+/// removed in place, keeping the first occurrence of each element, which
+/// takes quadratic time.
+impl<A: Array> From<SmallVec<A>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn from(mut elements: SmallVec<A>) -> SmallSet<A> {
+        let mut i = 0;
+        while i < elements.len() {
+            if elements[..i].contains(&elements[i]) {
+                elements.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        SmallSet { elements }
+    }
+}
+
+/// Builds a set from a `Vec`. As in `SmallVec::from_vec`, the choice of
+/// storage depends on the vector's capacity, not its length: if the capacity
+/// exceeds the inline size, the vector's heap buffer is kept and the set
+/// starts out spilled, even if its elements would fit inline; otherwise the
+/// elements are moved inline and the buffer is freed. Duplicates are then
+/// removed as for `From<SmallVec<A>>`, in `O(n^2)` time for `n` elements.
+impl<A: Array> From<Vec<A::Item>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    #[inline]
+    fn from(v: Vec<A::Item>) -> SmallSet<A> {
+        SmallSet::from(SmallVec::from_vec(v))
+    }
+}
+
+/// Builds a set from an array, inserting each element in order.
+impl<A: Array, const N: usize> From<[A::Item; N]> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    #[inline]
+    fn from(array: [A::Item; N]) -> SmallSet<A> {
+        IntoIterator::into_iter(array).collect()
+    }
+}
+
+/// Builds a set from a `HashSet`. Its elements are already distinct, so they
+/// are moved over without any comparisons, in the `HashSet`'s iteration
+/// order.
+#[cfg(feature = "std")]
+impl<A: Array, S> From<HashSet<A::Item, S>> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn from(set: HashSet<A::Item, S>) -> SmallSet<A> {
+        SmallSet {
+            elements: set.into_iter().collect(),
+        }
+    }
+}
+
+/// Returns the elements in order. An inline set is copied into a new `Vec`;
+/// a spilled set hands over its heap buffer.
+impl<A: Array> From<SmallSet<A>> for Vec<A::Item>
+where
+    A::Item: PartialEq + Eq,
+{
+    #[inline]
+    fn from(set: SmallSet<A>) -> Vec<A::Item> {
+        set.elements.into_vec()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<A: Array, S> From<SmallSet<A>> for HashSet<A::Item, S>
+where
+    A::Item: PartialEq + Eq + Hash,
+    S: BuildHasher + Default,
+{
+    fn from(set: SmallSet<A>) -> HashSet<A::Item, S> {
+        let mut out = HashSet::with_capacity_and_hasher(set.len(), S::default());
+        out.extend(set.elements);
+        out
+    }
+}
+
+/// Inserts each element in turn, so duplicates are dropped and the first
+/// occurrence of each element is kept, in iteration order. Up to 0.1.1,
+/// `collect` copied the iterator into the set as is, duplicates included.
+impl<A: Array> FromIterator<A::Item> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = A::Item>,
+    {
+        let mut set = SmallSet::new();
+        for elem in iter {
+            set.insert(elem);
+        }
+        set
+    }
+}
+
+/// Inserts each element not yet present. If the iterator's lower size bound
+/// shows that the set will spill, the heap buffer is allocated up front
+/// instead of growing while inserting. Since the bound counts duplicates, the
+/// up-front reservation is capped at four times the larger of the inline size
+/// and the current length; beyond that the buffer grows as elements are
+/// inserted.
+impl<A: Array> Extend<A::Item> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn extend<T>(&mut self, iter: T)
+    where
+        T: IntoIterator<Item = A::Item>,
+    {
+        let iter = iter.into_iter();
+        self.reserve_for_hint(iter.size_hint().0);
+        for elem in iter {
+            self.insert(elem);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate proptest;
+
+    #[cfg(feature = "std")]
+    use self::proptest::prelude::*;
+    use super::*;
+    #[cfg(feature = "std")]
+    use std::collections::HashSet;
+    use std::fmt::Write;
+    use std::string::ToString;
+
+    #[test]
+    #[allow(clippy::bool_comparison, clippy::map_clone)]
+    fn test_basic_set() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.insert(1) == true);
+        assert!(s.insert(2) == true);
+        assert!(s.insert(2) == false);
+        assert!(s.insert(3) == true);
+        assert!(s.insert(2) == false);
+        assert!(s.insert(3) == false);
+        assert!(s.contains(&1));
+        assert!(s.contains(&2));
+        assert!(s.contains(&3));
+        assert!(!s.contains(&4));
+        assert!(s.len() == 3);
+        assert!(s.iter().map(|r| *r).collect::<Vec<u32>>() == vec![1, 2, 3]);
+        s.clear();
+        assert!(!s.contains(&1));
+    }
+
+    #[test]
+    #[allow(clippy::bool_comparison, clippy::map_clone)]
+    fn test_remove() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.insert(1) == true);
+        assert!(s.insert(2) == true);
+        assert!(s.len() == 2);
+        assert!(s.contains(&1));
+        assert!(s.remove(&1) == true);
+        assert!(s.remove(&1) == false);
+        assert!(s.len() == 1);
+        assert!(!s.contains(&1));
+        assert!(s.insert(1) == true);
+        assert!(s.iter().map(|r| *r).collect::<Vec<u32>>() == vec![2, 1]);
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let mut s: SmallSet<[u32; 8]> = SmallSet::new();
+        for &i in &[5, 3, 7, 1, 3, 6] {
+            s.insert(i);
+        }
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![5, 3, 7, 1, 6]);
+        s.remove(&7);
+        s.remove(&5);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 1, 6]);
+        s.insert(5);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 1, 6, 5]);
+        assert!(!s.elements.spilled());
+    }
+
+    #[test]
+    fn test_insertion_order_across_spill() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        for &i in &[5, 3, 7, 1, 3, 6] {
+            s.insert(i);
+        }
+        assert!(s.elements.spilled());
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![5, 3, 7, 1, 6]);
+        s.remove(&7);
+        s.remove(&5);
+        s.insert(5);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 1, 6, 5]);
+        // Shrinking back inline keeps the order too.
+        s.remove(&1);
+        s.remove(&6);
+        s.shrink_to(0);
+        assert!(!s.elements.spilled());
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 5]);
+    }
+
+    #[test]
+    fn test_force_mode() {
+        let mut s: SmallSet<[u32; 4]> = (0..3).collect();
+        s.force_heap();
+        assert!(s.elements.spilled() && s.elements.capacity() == 8);
+        let ptr = s.elements.as_ptr();
+        s.force_heap();
+        assert!(s.elements.as_ptr() == ptr);
+        assert!(s.force_inline().is_ok());
+        assert!(!s.elements.spilled() && s.len() == 3);
+        s.extend(3..6);
+        assert!(s.force_inline().is_err() && s.elements.spilled());
+        let mut z: SmallSet<[u32; 0]> = SmallSet::new();
+        z.force_heap();
+        assert!(z.elements.spilled());
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut s: SmallSet<[u32; 4]> = (0..10).collect();
+        s.shrink_to_fit();
+        assert!(s.elements.spilled() && s.elements.capacity() == 10);
+        s.retain_top_k_by(3, |&e| e);
+        s.shrink_to_fit();
+        assert!(!s.elements.spilled());
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![7, 8, 9]);
+    }
+
+    #[derive(Debug)]
+    struct Flagged {
+        id: u32,
+        flags: u8,
+    }
+
+    impl PartialEq for Flagged {
+        fn eq(&self, other: &Flagged) -> bool {
+            self.id == other.id
+        }
+    }
+
+    impl Eq for Flagged {}
+
+    #[test]
+    fn test_insert_or_merge() {
+        let mut s: SmallSet<[Flagged; 2]> = SmallSet::new();
+        let merge = |old: &mut Flagged, new: Flagged| old.flags |= new.flags;
+        assert!(s.insert_or_merge(Flagged { id: 1, flags: 1 }, merge));
+        assert!(s.insert_or_merge(Flagged { id: 2, flags: 1 }, merge));
+        assert!(!s.insert_or_merge(Flagged { id: 1, flags: 4 }, merge));
+        assert!(s.len() == 2);
+        let flags: Vec<(u32, u8)> = s.iter().map(|f| (f.id, f.flags)).collect();
+        assert!(flags == vec![(1, 5), (2, 1)]);
+    }
+
+    #[test]
+    fn test_insert_detailed() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.insert_detailed(1) == InsertOutcome::Inserted);
+        assert!(s.insert_detailed(2) == InsertOutcome::Inserted);
+        assert!(s.insert_detailed(2) == InsertOutcome::AlreadyPresent);
+        let r = s.insert_detailed(3);
+        assert!(r.is_new() && r.spilled());
+        assert!(s.insert_detailed(4) == InsertOutcome::Inserted);
+        assert!(!s.insert_detailed(4).is_new());
+    }
+
+    #[cfg(feature = "union")]
+    #[test]
+    fn test_union_layout() {
+        use std::mem::size_of;
+        assert!(size_of::<SmallSet<[u64; 2]>>() == size_of::<[u64; 2]>() + size_of::<usize>());
+        assert!(size_of::<SmallSet<[u32; 8]>>() == size_of::<[u32; 8]>() + size_of::<usize>());
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.toggle(1));
+        assert!(s.toggle(2));
+        assert!(!s.toggle(1));
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![2]);
+        assert!(s.toggle(1));
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![2, 1]);
+    }
+
+    #[test]
+    fn test_contains_batch() {
+        let s: SmallSet<[u8; 4]> = vec![1, 3, 9].into_iter().collect();
+        let r: SmallVec<[bool; 4]> = s.contains_batch(&[0, 1, 9, 4]);
+        assert!(!r.spilled());
+        assert!(r.as_slice() == [false, true, true, false]);
+    }
+
+    #[test]
+    fn test_retain_top_k_by() {
+        let mut s: SmallSet<[i32; 4]> = vec![3, -7, 1, 5, -2, 4].into_iter().collect();
+        s.retain_top_k_by(3, |e| e.abs());
+        assert!(s.iter().copied().collect::<Vec<i32>>() == vec![-7, 5, 4]);
+        let mut s: SmallSet<[u32; 4]> = vec![1, 2, 3, 4].into_iter().collect();
+        s.retain_top_k_by(2, |_| 0);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1, 2]);
+        s.retain_top_k_by(5, |e| *e);
+        assert!(s.len() == 2);
+        s.retain_top_k_by(0, |e| *e);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let s: SmallSet<[String; 2]> = vec!["a", "b", "c"].into_iter().map(String::from).collect();
+        let mut it = s.clone().into_iter();
+        assert!(it.len() == 3);
+        assert!(it.next_back() == Some("c".to_string()));
+        assert!(it.as_slice() == ["a", "b"]);
+        let mut out = vec![];
+        for e in s {
+            out.push(e);
+        }
+        assert!(out == vec!["a", "b", "c"]);
+        let t: SmallSet<[u32; 4]> = (0..3).collect();
+        assert!(t.into_iter().map(|e| e * 2).sum::<u32>() == 6);
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        s.extend(vec![1, 2, 1]);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1, 2]);
+        s.extend_cloned(&[2, 3]);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1, 2, 3]);
+        // The lower bound of 20 is capped at 4 * 2, so the set spills
+        // straight to 8 slots: not 32 as the full hint would give, and not 4
+        // as growing one element at a time would give for three elements.
+        let mut t: SmallSet<[u32; 2]> = SmallSet::new();
+        t.extend((0..20).map(|i| i % 3));
+        assert!(t.elements.spilled() && t.elements.capacity() == 8);
+        assert!(t.len() == 3);
+        // Past the cap, inserts grow the buffer as usual.
+        t.extend(0..20);
+        assert!(t.elements.capacity() == 32 && t.len() == 20);
+    }
+
+    #[test]
+    fn test_collect_from() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        s.collect_from(vec![1, 2]);
+        assert!(!s.elements.spilled() && s.len() == 2);
+        // The lower bound of 10 is capped at 4 * 2 slots, reserved up front.
+        s.collect_from((0..10).map(|i| i % 3));
+        assert!(s.elements.spilled() && s.elements.capacity() == 8);
+        assert!(s.len() == 3);
+        let ptr = s.elements.as_ptr();
+        s.collect_from(vec![5, 4, 5, 3]);
+        assert!(s.elements.as_ptr() == ptr);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![5, 4, 3]);
+    }
+
+    #[test]
+    fn test_extract_if() {
+        let mut s: SmallSet<[String; 2]> = vec!["a", "bb", "c", "dd"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        assert!(s.extract_if(|e| e.len() == 2).next() == Some("bb".to_string()));
+        // "dd" was not reached, so it stays.
+        assert!(s.iter().map(|e| e.as_str()).collect::<Vec<&str>>() == vec!["a", "c", "dd"]);
+        assert!(s.extract_if(|_| true).count() == 3);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hash() {
+        use std::collections::HashMap;
+
+        let a: SmallSet<[u32; 2]> = vec![1, 2, 3].into_iter().collect();
+        let b: SmallSet<[u32; 2]> = vec![3, 1, 2].into_iter().collect();
+        let c: SmallSet<[u32; 2]> = vec![1, 2].into_iter().collect();
+        let hash = |s: &SmallSet<[u32; 2]>| {
+            let mut h = DefaultHasher::new();
+            s.hash(&mut h);
+            h.finish()
+        };
+        assert!(hash(&a) == hash(&b) && hash(&a) != hash(&c));
+        let mut memo = HashMap::new();
+        memo.insert(a, "abc");
+        assert!(memo.get(&b) == Some(&"abc") && !memo.contains_key(&c));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_eq_other_collections() {
+        let s: SmallSet<[u32; 2]> = vec![3, 1, 2].into_iter().collect();
+        assert!(s == [1, 2, 3] && s == [2, 2, 3, 1]);
+        assert!(s != [1, 2] && s != [1, 2, 3, 4]);
+        assert!(s == *[3, 2, 1].as_slice() && s != *[1, 2, 4].as_slice());
+        let h: HashSet<u32> = vec![1, 2, 3].into_iter().collect();
+        assert!(s == h);
+        let e: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(e != [0] && e != h);
+    }
+
+    #[test]
+    fn test_from_conversions() {
+        let s: SmallSet<[u32; 2]> = SmallSet::from([3, 1, 3, 2]);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 1, 2]);
+        let v = vec![5, 5, 6, 5, 7];
+        let ptr = v.as_ptr();
+        let t: SmallSet<[u32; 2]> = v.into();
+        assert!(t.elements.as_ptr() == ptr && t.elements.as_slice() == [5, 6, 7]);
+        let u: SmallSet<[u32; 4]> = smallvec::smallvec![1, 1, 1].into();
+        assert!(u.len() == 1 && !u.elements.spilled());
+        // Storage follows the vector's capacity, not its length.
+        let mut w = Vec::with_capacity(16);
+        w.extend([1, 2]);
+        let w: SmallSet<[u32; 4]> = w.into();
+        assert!(w.len() == 2 && w.elements.spilled());
+        let v: Vec<u32> = t.into();
+        assert!(v == vec![5, 6, 7]);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hash_set_conversions() {
+        let s: SmallSet<[u32; 2]> = SmallSet::from([3, 1, 2]);
+        let h: HashSet<u32> = s.clone().into();
+        assert!(s == h);
+        let back: SmallSet<[u32; 2]> = h.into();
+        assert!(back == s);
+    }
+
+    #[test]
+    fn test_smallset_macro() {
+        let e: SmallSet<[u32; 2]> = smallset![];
+        assert!(e.is_empty());
+        let s: SmallSet<[u32; 2]> = smallset![3, 1, 3,];
+        assert!(s.elements.as_slice() == [3, 1] && !s.elements.spilled());
+        let x = 7;
+        let t = smallset![Vec<u32>; 1 => vec![x], vec![], vec![x]];
+        assert!(t.len() == 2 && t.elements.spilled());
+        let u = smallset![&'static str; 4 => "a", "b"];
+        assert!(u == ["b", "a"]);
+        let v: SmallSet<[[u8; 2]; 2]> = smallset![[1, 2], [x as u8, 0]];
+        assert!(v.contains(&[7, 0]));
+    }
+
+    #[test]
+    fn test_capacity() {
+        let s: SmallSet<[u32; 4]> = SmallSet::with_capacity(3);
+        assert!(!s.spilled() && s.capacity() == 4 && s.inline_size() == 4);
+        let mut t: SmallSet<[u32; 4]> = SmallSet::with_capacity(100);
+        assert!(t.spilled() && t.capacity() >= 100 && t.inline_size() == 4);
+        let ptr = t.elements.as_ptr();
+        t.extend(0..100);
+        assert!(t.elements.as_ptr() == ptr);
+    }
+
+    #[test]
+    fn test_eq_by_sorting() {
+        let a: SmallSet<[u32; 4]> = (0..100).collect();
+        let b: SmallSet<[u32; 2]> = (0..50).chain((50..100).rev()).collect();
+        let c: SmallSet<[u32; 2]> = (1..101).collect();
+        assert!(a.eq_by_sorting(&b) && a == b);
+        assert!(!a.eq_by_sorting(&c) && a != c);
+        assert!(!a.eq_by_sorting(&SmallSet::<[u32; 4]>::new()));
+        let e: SmallSet<[u32; 4]> = SmallSet::new();
+        assert!(e.eq_by_sorting(&SmallSet::<[u32; 8]>::new()));
+    }
+
+    #[test]
+    fn test_extend_duplicate_hint() {
+        let mut s: SmallSet<[u32; 8]> = SmallSet::new();
+        s.extend(std::iter::repeat_n(7, 1_000_000));
+        assert!(s.len() == 1 && s.capacity() <= 32);
+        s.collect_from(std::iter::repeat_n(3, 1_000_000));
+        assert!(s.len() == 1 && s.capacity() <= 32);
+        // A hint within the cap still sizes the buffer once.
+        let mut t: SmallSet<[u32; 8]> = SmallSet::new();
+        t.extend(0..20);
+        assert!(t.spilled() && t.capacity() == 32);
+        // Zero-sized elements do not overflow the cap.
+        let mut z: SmallSet<[(); 4]> = SmallSet::new();
+        z.extend(std::iter::repeat_n((), 10));
+        assert!(z.len() == 1);
+    }
+
+    #[test]
+    fn test_pop() {
+        let mut work: SmallSet<[u32; 2]> = vec![1, 2, 3].into_iter().collect();
+        let mut order = vec![];
+        while let Some(e) = work.pop() {
+            order.push(e);
+            if e == 3 {
+                work.insert(4);
+            }
+        }
+        assert!(order == vec![3, 4, 2, 1]);
+        assert!(work.pop().is_none());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut s: SmallSet<[u32; 2]> = (0..6).collect();
+        let limit = 4;
+        s.retain(|&e| e < limit && e != 1);
+        assert!(s.as_slice() == [0, 2, 3]);
+        let mut seen = vec![];
+        s.retain(|e| {
+            seen.push(*e);
+            true
+        });
+        assert!(seen == vec![0, 2, 3] && s.len() == 3);
+    }
+
+    #[test]
+    fn test_try_retain() {
+        let mut s: SmallSet<[i32; 4]> = vec![1, 2, 3, 4, -5, 6].into_iter().collect();
+        let r = s.try_retain(|&e| if e < 0 { Err(e) } else { Ok(e % 2 == 0) });
+        assert!(r == Err(-5));
+        assert!(s.iter().copied().collect::<Vec<i32>>() == vec![2, 4, -5, 6]);
+        assert!(s.try_retain(|&e| Ok::<_, ()>(e > 2)).is_ok());
+        assert!(s.iter().copied().collect::<Vec<i32>>() == vec![4, 6]);
+    }
+
+    #[test]
+    fn test_min_max_by_key() {
+        let s: SmallSet<[i32; 2]> = vec![3, -7, 7, 1].into_iter().collect();
+        assert!(s.min_by_key(|e| e.abs()) == Some(&1));
+        assert!(s.max_by_key(|e| e.abs()) == Some(&7));
+        assert!(s.arg_min_by_key(|e| e.abs()) == Some((&1, 1)));
+        assert!(s.arg_max_by_key(|e| e.abs()) == Some((&7, 7)));
+        let empty: SmallSet<[i32; 2]> = SmallSet::new();
+        assert!(empty.max_by_key(|e| *e).is_none());
+        assert!(empty.arg_min_by_key(|e| *e).is_none());
+    }
+
+    #[test]
+    fn test_shares_at_least() {
+        let a: SmallSet<[u32; 4]> = vec![1, 2, 3, 4].into_iter().collect();
+        let b: SmallSet<[u32; 2]> = vec![4, 9, 2].into_iter().collect();
+        assert!(a.shares_at_least(&b, 0));
+        assert!(a.shares_at_least(&b, 2));
+        assert!(!a.shares_at_least(&b, 3));
+        assert!(b.shares_at_least(&a, 2));
+        assert!(!a.shares_at_least(&SmallSet::<[u32; 1]>::new(), 1));
+    }
+
+    #[test]
+    fn test_disjoint_union() {
+        let a: SmallSet<[u32; 2]> = vec![1, 2].into_iter().collect();
+        let b: SmallSet<[u32; 2]> = vec![3, 4].into_iter().collect();
+        let u = a.clone().disjoint_union(b).unwrap();
+        assert!(u.iter().copied().collect::<Vec<u32>>() == vec![1, 2, 3, 4]);
+        let c: SmallSet<[u32; 2]> = vec![5, 2, 1].into_iter().collect();
+        assert!(*a.disjoint_union(c).unwrap_err().element() == 2);
+    }
+
+    #[test]
+    fn test_exactly_one() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.exactly_one().unwrap_err().is_empty());
+        s.insert(4);
+        assert!(s.exactly_one() == Ok(&4));
+        s.insert(5);
+        let e = s.exactly_one().unwrap_err();
+        assert!(e.len() == 2);
+        assert!(e.to_string() == "expected exactly one element, found 2");
+        let s = s.try_into_single().unwrap_err().into_inner();
+        assert!(s.len() == 2);
+        let s: SmallSet<[String; 2]> = vec!["x".to_string()].into_iter().collect();
+        assert!(s.try_into_single().ok() == Some("x".to_string()));
+    }
+
+    #[test]
+    fn test_get() {
+        let s: SmallSet<[String; 1]> = vec!["a".to_string(), "b".to_string()].into_iter().collect();
+        assert!(s.get("b").map(|e| e.as_str()) == Some("b"));
+        assert!(s.get(&"a".to_string()).is_some());
+        assert!(s.get("c").is_none());
+    }
+
+    #[test]
+    fn test_eq() {
+        let a: SmallSet<[u32; 2]> = vec![1, 2, 3, 4].into_iter().collect();
+        let b: SmallSet<[u32; 8]> = vec![1, 2, 4, 3].into_iter().collect();
+        let c: SmallSet<[u32; 8]> = vec![1, 2, 4, 5].into_iter().collect();
+        assert!(a == b);
+        assert!(b == a);
+        assert!(a != c);
+        assert!(a != SmallSet::<[u32; 2]>::new());
+        assert!(SmallSet::<[u32; 2]>::new() == SmallSet::<[u32; 4]>::new());
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Unit;
+
+    /// A zero-sized type whose values never compare equal.
+    #[derive(Debug)]
+    struct Distinct;
+
+    impl PartialEq for Distinct {
+        fn eq(&self, _: &Distinct) -> bool {
+            false
+        }
+    }
+
+    impl Eq for Distinct {}
+
+    #[test]
+    fn test_zero_sized_partial_eq() {
+        // Membership always goes through `PartialEq`, even for zero-sized
+        // elements, so `contains` agrees with `insert` and `remove`.
+        let mut s: SmallSet<[Distinct; 2]> = SmallSet::new();
+        assert!(s.insert(Distinct) && s.insert(Distinct));
+        assert!(s.len() == 2 && !s.contains(&Distinct));
+        assert!(!s.remove(&Distinct) && s.get(&Distinct).is_none());
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let mut s: SmallSet<[(); 2]> = SmallSet::new();
+        assert!(!s.contains(&()));
+        assert!(s.insert(()));
+        assert!(!s.insert(()));
+        assert!(s.len() == 1 && s.contains(&()));
+        assert!(s.iter().count() == 1);
+        assert!(s.remove(&()) && s.is_empty());
+
+        let mut u: SmallSet<[Unit; 0]> = (0..100).map(|_| Unit).collect();
+        assert!(u.len() == 1 && !u.elements.spilled());
+        u.reserve(1000);
+        u.shrink_to(0);
+        assert!(!u.elements.spilled());
+        assert!(!u.toggle(Unit) && u.is_empty());
+        assert!(u.drain_sorted().next().is_none());
+    }
+
+    #[test]
+    fn test_zero_inline_capacity() {
+        let mut s: SmallSet<[u32; 0]> = SmallSet::new();
+        assert!(s.is_inline_full() && s.will_spill_on_insert());
+        assert!(s.insert_within_capacity(1) == Err(1));
+        assert!(s.insert_detailed(1) == InsertOutcome::InsertedAndSpilled);
+        assert!(s.insert(2) && !s.insert(1));
+        assert!(s.elements.spilled() && s.len() == 2);
+        assert!(s.remove(&1) && s.remove(&2) && s.is_empty());
+        s.shrink_to(0);
+        assert!(!s.elements.spilled() && s.is_inline_full());
+        let t: SmallSet<[String; 0]> = vec!["a".to_string()].into_iter().collect();
+        assert!(t.clone().try_into_single().ok() == Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_get_many() {
+        let s: SmallSet<[String; 2]> = vec!["a", "b", "c"].into_iter().map(String::from).collect();
+        let [a, x, c, a2] = s.get_many(["a", "x", "c", "a"]);
+        assert!(a.map(|e| e.as_str()) == Some("a") && a2 == a);
+        assert!(x.is_none() && c.map(|e| e.as_str()) == Some("c"));
+        let none: [Option<&String>; 0] = s.get_many::<str, 0>([]);
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_in_place_ops() {
+        let mut s: SmallSet<[u32; 2]> = vec![1, 2, 3].into_iter().collect();
+        let other: SmallSet<[u32; 4]> = vec![3, 4].into_iter().collect();
+        assert!(s.union_with(&other));
+        assert!(!s.union_with(&other));
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1, 2, 3, 4]);
+        assert!(s.difference_with(&vec![2].into_iter().collect::<SmallSet<[u32; 1]>>()));
+        assert!(!s.difference_with(&vec![9].into_iter().collect::<SmallSet<[u32; 1]>>()));
+        assert!(s.intersection_with(&other));
+        assert!(!s.intersection_with(&other));
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 4]);
+        assert!(s.extend_from_set(vec![4, 5].into_iter().collect::<SmallSet<[u32; 2]>>()));
+        assert!(!s.extend_from_set(other));
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_subset_predicates() {
+        let a: SmallSet<[u32; 2]> = vec![1, 2].into_iter().collect();
+        let b: SmallSet<[u32; 4]> = vec![3, 2, 1].into_iter().collect();
+        let c: SmallSet<[u32; 2]> = vec![2, 1].into_iter().collect();
+        let empty: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(a.is_subset(&b) && a.is_proper_subset(&b) && !b.is_subset(&a));
+        assert!(b.is_superset(&a) && b.is_proper_superset(&a) && !a.is_superset(&b));
+        assert!(a.is_subset(&c) && a.is_superset(&c));
+        assert!(!a.is_proper_subset(&c) && !a.is_proper_superset(&c));
+        assert!(empty.is_subset(&a) && empty.is_disjoint(&a) && a.is_disjoint(&empty));
+        assert!(!a.is_disjoint(&b));
+        let d: SmallSet<[u32; 2]> = vec![7, 8, 9].into_iter().collect();
+        assert!(d.is_disjoint(&b));
+    }
+
+    #[test]
+    fn test_operators() {
+        let a: SmallSet<[u32; 2]> = vec![1, 2, 3].into_iter().collect();
+        let b: SmallSet<[u32; 4]> = vec![4, 3, 2].into_iter().collect();
+        let v = |s: SmallSet<[u32; 2]>| s.iter().copied().collect::<Vec<u32>>();
+        assert!(v(&a | &b) == vec![1, 2, 3, 4]);
+        assert!(v(&a & &b) == vec![2, 3]);
+        assert!(v(&a - &b) == vec![1]);
+        assert!(v(&a ^ &b) == vec![1, 4]);
+        let mut c = a.clone();
+        c |= &b;
+        c -= &a;
+        assert!(v(c.clone()) == vec![4]);
+        c ^= &b;
+        assert!(v(c.clone()) == vec![3, 2]);
+        c &= &a;
+        assert!(v(c) == vec![3, 2]);
+    }
+
+    #[test]
+    fn test_symmetric_difference_with() {
+        let mut s: SmallSet<[u32; 2]> = vec![1, 2, 3].into_iter().collect();
+        let other: SmallSet<[u32; 4]> = vec![4, 2, 5].into_iter().collect();
+        assert!(s.symmetric_difference_with(&other));
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1, 3, 4, 5]);
+        assert!(s.symmetric_difference_with(&other));
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1, 3, 2]);
+        assert!(!s.symmetric_difference_with(&SmallSet::<[u32; 1]>::new()));
+        s.symmetric_difference_with(&s.clone());
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_as_mut_slice() {
+        let mut s: SmallSet<[u32; 4]> = vec![3, 1, 2].into_iter().collect();
+        unsafe {
+            s.as_mut_slice().sort();
+            for e in s.as_mut_slice() {
+                *e *= 10;
+            }
+        }
+        assert!(s.as_slice() == [10, 20, 30]);
+        assert!(s.contains(&20) && !s.contains(&2));
+    }
+
+    #[test]
+    fn test_complement() {
+        let s: SmallSet<[u8; 4]> = vec![1, 3, 9].into_iter().collect();
+        let c = s.complement(0..5);
+        assert!(c.iter().copied().collect::<Vec<u8>>() == vec![0, 2, 4]);
+        assert!(c.complement(0..5).iter().copied().collect::<Vec<u8>>() == vec![1, 3]);
+    }
+
+    #[test]
+    fn test_set_ops_into() {
+        let a: SmallSet<[u32; 4]> = vec![1, 2, 3].into_iter().collect();
+        let b: SmallSet<[u32; 4]> = vec![4, 3, 5].into_iter().collect();
+        let mut out: SmallSet<[u32; 2]> = vec![9, 8, 7].into_iter().collect();
+        assert!(out.elements.spilled());
+        let cap = out.elements.capacity();
+
+        a.union_into(&b, &mut out);
+        assert!(out.iter().copied().collect::<Vec<u32>>() == vec![1, 2, 3, 4, 5]);
+        a.intersection_into(&b, &mut out);
+        assert!(out.iter().copied().collect::<Vec<u32>>() == vec![3]);
+        a.difference_into(&b, &mut out);
+        assert!(out.iter().copied().collect::<Vec<u32>>() == vec![1, 2]);
+        b.difference_into(&a, &mut out);
+        assert!(out.iter().copied().collect::<Vec<u32>>() == vec![4, 5]);
+        assert!(out.elements.spilled() && out.elements.capacity() >= cap);
+    }
+
+    #[test]
+    fn test_into_array() {
+        let s: SmallSet<[u32; 2]> = vec![4, 2, 7, 2].into_iter().collect();
+        let s = s.into_array::<2>().unwrap_err();
+        assert!(s.len() == 3);
+        let arr: [u32; 3] = s.into_array().unwrap();
+        assert!(arr == [4, 2, 7]);
+        let s: SmallSet<[String; 4]> = vec!["a".to_string()].into_iter().collect();
+        let arr = <[String; 1]>::try_from(s).unwrap();
+        assert!(arr == ["a".to_string()]);
+    }
+
+    #[test]
+    fn test_delimited() {
+        type S = SmallSet<[u32; 2]>;
+        let s = S::parse_delimited(" 3,1 , 2", ',').unwrap();
+        assert!(s.format_delimited(", ") == "3, 1, 2");
+        let t = S::parse_delimited(&s.format_delimited(";"), ';').unwrap();
+        assert!(t.iter().eq(s.iter()));
+        assert!(S::parse_delimited("  ", ',').unwrap().is_empty());
+        assert!(S::parse_delimited("", ',')
+            .unwrap()
+            .format_delimited(",")
+            .is_empty());
+        match S::parse_delimited("1,x", ',') {
+            Err(ParseError::Invalid(_)) => {}
+            _ => panic!("expected an invalid item"),
+        }
+        match S::parse_delimited("1,2,1", ',') {
+            Err(ParseError::Duplicate(e)) => assert!(*e.element() == 1),
+            _ => panic!("expected a duplicate"),
+        }
+    }
+
+    #[test]
+    fn test_bitmask() {
+        let s: SmallSet<[u32; 2]> = vec![5, 0, 63].into_iter().collect();
+        let mask = s.to_bitmask_u64().unwrap();
+        assert!(mask == (1 << 63) | (1 << 5) | 1);
+        let t: SmallSet<[u32; 2]> = SmallSet::from_bitmask_u64(mask);
+        assert!(t.iter().copied().collect::<Vec<u32>>() == vec![0, 5, 63]);
+
+        let s: SmallSet<[i32; 2]> = vec![1, 64, -1].into_iter().collect();
+        assert!(*s.to_bitmask_u64().unwrap_err().element() == 64);
+        assert!(*s.to_bitmask_u128().unwrap_err().element() == -1);
+        let s: SmallSet<[u16; 2]> = vec![127, 1000].into_iter().collect();
+        assert!(*s.to_bitmask_u128().unwrap_err().element() == 1000);
+        let u: SmallSet<[u8; 2]> = SmallSet::from_bitmask_u128(1 << 127);
+        assert!(u.iter().copied().collect::<Vec<u8>>() == vec![127]);
+        assert!(SmallSet::<[u8; 2]>::from_bitmask_u64(0).is_empty());
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut a: SmallSet<[u32; 2]> = vec![3, 1].into_iter().collect();
+        let mut b: SmallSet<[u32; 2]> = vec![5, 3, 1].into_iter().collect();
+        a.sort();
+        b.sort();
+        assert!(format!("{:?}", a) == "[1, 3]");
+        assert!(b.as_slice() == [1, 3, 5]);
+    }
+
+    #[test]
+    fn test_drain_sorted() {
+        let mut s: SmallSet<[u32; 2]> = vec![5, 1, 4, 2].into_iter().collect();
+        assert!(s.drain_sorted().collect::<Vec<u32>>() == vec![1, 2, 4, 5]);
+        assert!(s.is_empty());
+        let mut s: SmallSet<[String; 4]> =
+            vec!["b".to_string(), "a".to_string()].into_iter().collect();
+        assert!(s.drain_sorted().next() == Some("a".to_string()));
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_any_element() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.any_element().is_none());
+        s.insert(4);
+        s.insert(5);
+        assert!(s.any_element() == Some(&4));
+    }
+
+    #[test]
+    fn test_will_spill_on_insert() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        s.insert(1);
+        assert!(!s.will_spill_on_insert());
+        s.insert(2);
+        assert!(s.is_inline_full());
+        assert!(s.will_spill_on_insert());
+        assert!(!s.elements.spilled());
+        s.insert(3);
+        assert!(s.elements.spilled());
+        assert!(!s.is_inline_full());
+        assert!(!s.will_spill_on_insert());
+    }
+
+    #[test]
+    fn test_try_insert() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.try_insert(1).is_ok());
+        let err = s.try_insert(1).unwrap_err();
+        assert!(*err.element() == 1);
+        assert!(err.to_string() == "element already present");
+        let err: errors::Error<u32> = err.into();
+        assert!(err.into_element() == 1);
+        assert!(s.len() == 1);
+    }
+
+    #[test]
+    fn test_insert_within_capacity() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.insert_within_capacity(1) == Ok(true));
+        assert!(s.insert_within_capacity(2) == Ok(true));
+        assert!(s.insert_within_capacity(2) == Ok(false));
+        assert!(s.insert_within_capacity(3) == Err(3));
+        assert!(!s.elements.spilled());
+        assert!(s.len() == 2);
+    }
+
+    #[test]
+    fn test_reserve() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        s.insert(1);
+        s.reserve_exact(1);
+        assert!(!s.elements.spilled());
+        s.reserve_exact(4);
+        assert!(s.elements.capacity() == 5);
+        s.reserve(5);
+        assert!(s.elements.capacity() >= 6);
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1]);
+    }
+
+    #[test]
+    fn test_spill_capacity() {
+        let mut s: SmallSet<[u32; 8]> = (0..8).collect();
+        assert!(!s.elements.spilled());
+        s.insert(8);
+        assert!(s.elements.spilled() && s.elements.capacity() == 16);
+        for i in 9..16 {
+            s.insert(i);
+        }
+        assert!(s.elements.capacity() == 16);
+        s.insert(16);
+        assert!(s.elements.capacity() == 32);
+
+        let mut s: SmallSet<[u32; 8]> = SmallSet::new();
+        s.reserve_exact(40);
+        assert!(s.elements.spilled() && s.elements.capacity() == 40);
+    }
+
+    #[test]
+    fn test_fallible() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(s.fallible_insert(1) == Ok(true));
+        assert!(s.fallible_insert(1) == Ok(false));
+        assert!(s.try_extend(vec![2, 3, 1]).is_ok());
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1, 2, 3]);
+        assert!(s.try_reserve(4).is_ok());
+        assert!(s.elements.capacity() >= 7);
+        assert!(s.try_reserve_exact(usize::MAX).is_err());
+        assert!(s.try_reserve(usize::MAX).is_err());
+        assert!(s.len() == 3);
+    }
+
+    #[test]
+    fn test_shrink_to() {
+        let mut s: SmallSet<[u32; 2]> = (0..10).collect();
+        assert!(s.elements.spilled());
+        for i in 3..10 {
+            s.remove(&i);
+        }
+        s.shrink_to(4);
+        assert!(s.elements.capacity() == 4);
+        s.shrink_to(8);
+        assert!(s.elements.capacity() == 4);
+        s.remove(&2);
+        s.shrink_to(0);
+        assert!(!s.elements.spilled());
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![0, 1]);
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        s.insert(1);
+        s.insert(2);
+        let c = s.clone();
+        assert!(c.contains(&1));
+        assert!(c.contains(&2));
+        assert!(!c.contains(&3));
+    }
+
+    #[test]
+    fn test_debug() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        s.insert(1);
+        s.insert(2);
+        let mut buf = String::new();
+        write!(buf, "{:?}", s).unwrap();
+        assert!(&buf == "[1, 2]");
+    }
+
+    #[test]
+    fn test_debug_truncated() {
+        let s: SmallSet<[u32; 2]> = (0..1000).collect();
+        assert!(format!("{:?}", s.debug_truncated(3)) == "[0, 1, 2, \u{2026} (+997 more)]");
+        assert!(format!("{:?}", s.debug_truncated(1000)) == format!("{:?}", s));
+        let empty: SmallSet<[u32; 2]> = SmallSet::new();
+        assert!(format!("{:?}", empty.debug_truncated(0)) == "[]");
+    }
+
+    #[test]
+    fn test_from_range() {
+        let s: SmallSet<[u64; 4]> = SmallSet::from_range(3..7);
+        assert!(!s.elements.spilled());
+        assert!(s.iter().copied().collect::<Vec<u64>>() == vec![3, 4, 5, 6]);
+        let s: SmallSet<[i32; 4]> = SmallSet::from_range(-5..5);
+        assert!(s.len() == 10);
+        assert!(s.contains(&-5) && s.contains(&4) && !s.contains(&5));
+        let s: SmallSet<[u8; 4]> = SmallSet::from_range(5..5);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn test_fromiter() {
+        let s: SmallSet<[usize; 4]> = vec![1, 2, 3, 4].into_iter().collect();
+        assert!(s.len() == 4);
+    }
+
+    #[test]
+    fn test_fromiter_dedup() {
+        let s: SmallSet<[usize; 4]> = vec![1, 2, 1, 3, 2].into_iter().collect();
+        assert!(s.len() == 3);
+        assert!(s.iter().copied().collect::<Vec<usize>>() == vec![1, 2, 3]);
+    }
+
+    /// Applies every sequence of up to `depth` operations over the universe
+    /// `0..4` to `set`, checking it against `model` (which records insertion
+    /// order) after each step.
+    fn exhaustive(set: &SmallSet<[u8; 2]>, model: &[u8], depth: usize) {
+        if depth == 0 {
+            return;
+        }
+        for x in 0..4u8 {
+            let mut s = set.clone();
+            let mut m = model.to_vec();
+            let inserted = s.insert(x);
+            assert_eq!(inserted, !m.contains(&x));
+            if inserted {
+                m.push(x);
+            }
+            check_exhaustive(&s, &m);
+            exhaustive(&s, &m, depth - 1);
+
+            let mut s = set.clone();
+            let mut m = model.to_vec();
+            let removed = s.remove(&x);
+            assert_eq!(removed, m.contains(&x));
+            m.retain(|&e| e != x);
+            check_exhaustive(&s, &m);
+            exhaustive(&s, &m, depth - 1);
+        }
+        let mut s = set.clone();
+        s.shrink_to(0);
+        assert_eq!(s.elements.spilled(), model.len() > 2);
+        check_exhaustive(&s, model);
+        exhaustive(&s, model, depth - 1);
+    }
+
+    fn check_exhaustive(s: &SmallSet<[u8; 2]>, model: &[u8]) {
+        assert_eq!(s.len(), model.len());
+        assert_eq!(s.iter().copied().collect::<Vec<u8>>(), model);
+        for x in 0..4 {
+            assert_eq!(s.contains(&x), model.contains(&x));
+        }
+        if s.len() > 2 {
+            assert!(s.elements.spilled());
+        }
+    }
+
+    #[test]
+    fn test_exhaustive_sequences() {
+        exhaustive(&SmallSet::new(), &[], 6);
+    }
+
+    // The model-based tests use `HashSet` as the oracle.
+    #[cfg(feature = "std")]
+    #[derive(Clone, Debug)]
+    enum Op {
+        Insert(u8),
+        TryInsert(u8),
+        Remove(u8),
+        Clear,
+        ShrinkTo(usize),
+        Collect(Vec<u8>),
+    }
+
+    #[cfg(feature = "std")]
+    fn op_strategy() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            4 => (0u8..16).prop_map(Op::Insert),
+            2 => (0u8..16).prop_map(Op::TryInsert),
+            3 => (0u8..16).prop_map(Op::Remove),
+            1 => Just(Op::Clear),
+            1 => (0usize..20).prop_map(Op::ShrinkTo),
+            1 => proptest::collection::vec(0u8..16, 0..20).prop_map(Op::Collect),
+        ]
+    }
+
+    #[cfg(feature = "std")]
+    fn check_equivalent(
+        small: &SmallSet<[u8; 1]>,
+        large: &SmallSet<[u8; 32]>,
+        oracle: &HashSet<u8>,
+    ) {
+        assert_eq!(small.len(), oracle.len());
+        assert_eq!(large.len(), oracle.len());
+        assert_eq!(
+            small.iter().collect::<Vec<_>>(),
+            large.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(small.iter().copied().collect::<HashSet<u8>>(), *oracle);
+        for i in 0..16 {
+            assert_eq!(small.contains(&i), oracle.contains(&i));
+            assert_eq!(large.contains(&i), oracle.contains(&i));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    proptest! {
+        #[test]
+        fn test_model_equivalence(ops in proptest::collection::vec(op_strategy(), 0..64)) {
+            let mut small: SmallSet<[u8; 1]> = SmallSet::new();
+            let mut large: SmallSet<[u8; 32]> = SmallSet::new();
+            let mut oracle: HashSet<u8> = HashSet::new();
+            for op in ops {
+                match op {
+                    Op::Insert(x) => {
+                        let expected = oracle.insert(x);
+                        prop_assert_eq!(small.insert(x), expected);
+                        prop_assert_eq!(large.insert(x), expected);
+                    }
+                    Op::TryInsert(x) => {
+                        let expected = oracle.insert(x);
+                        prop_assert_eq!(small.try_insert(x).is_ok(), expected);
+                        prop_assert_eq!(large.try_insert(x).is_ok(), expected);
+                    }
+                    Op::Remove(x) => {
+                        let expected = oracle.remove(&x);
+                        prop_assert_eq!(small.remove(&x), expected);
+                        prop_assert_eq!(large.remove(&x), expected);
+                    }
+                    Op::Clear => {
+                        oracle.clear();
+                        small.clear();
+                        large.clear();
+                    }
+                    Op::ShrinkTo(n) => {
+                        small.shrink_to(n);
+                        large.shrink_to(n);
+                    }
+                    Op::Collect(v) => {
+                        oracle = v.iter().copied().collect();
+                        small = v.iter().copied().collect();
+                        large = v.iter().copied().collect();
+                    }
+                }
+                check_equivalent(&small, &large, &oracle);
+            }
+        }
     }
 }
@@ -0,0 +1,234 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! An append-only small set that can grow through a shared reference.
+
+use smallvec::SmallVec;
+use std::boxed::Box;
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::vec::Vec;
+
+/// A `OnceSmallSet` is an append-only set that allows `insert` through a
+/// shared reference, in the style of `elsa::FrozenVec`. It is meant for
+/// caches and interners embedded in structures that are otherwise immutable.
+///
+/// Each element is boxed, so it never moves once inserted, even when the set
+/// spills to the heap. Elements can only be removed through `&mut self`, so a
+/// reference returned by `insert` or `get` stays valid for as long as the
+/// shared borrow of the set it came from. Up to `N` boxes are stored inline.
+///
+/// The elements' `PartialEq` impl must not access the set it is being compared
+/// within; doing so panics. Like `Cell`, a `OnceSmallSet` is not `Sync`.
+///
+/// ```
+/// use smallset::OnceSmallSet;
+///
+/// let names: OnceSmallSet<String, 4> = OnceSmallSet::new();
+/// let a = names.insert("a".to_string());
+/// let b = names.insert("b".to_string());
+/// assert!(std::ptr::eq(a, names.insert("a".to_string())));
+/// assert!(a == "a" && b == "b");
+/// ```
+pub struct OnceSmallSet<T, const N: usize> {
+    elements: UnsafeCell<SmallVec<[Box<T>; N]>>,
+    in_use: Cell<bool>,
+}
+
+/// Clears the `in_use` flag when dropped, including on unwind.
+struct InUse<'a>(&'a Cell<bool>);
+
+impl<'a> Drop for InUse<'a> {
+    fn drop(&mut self) {
+        self.0.set(false);
+    }
+}
+
+impl<T: PartialEq + Eq, const N: usize> OnceSmallSet<T, N> {
+    /// Creates a new, empty `OnceSmallSet`.
+    #[inline]
+    pub fn new() -> OnceSmallSet<T, N> {
+        OnceSmallSet {
+            elements: UnsafeCell::new(SmallVec::new()),
+            in_use: Cell::new(false),
+        }
+    }
+
+    /// Runs `f` on the element storage, panicking if it is already in use.
+    fn with<R, F: FnOnce(&mut SmallVec<[Box<T>; N]>) -> R>(&self, f: F) -> R {
+        assert!(!self.in_use.get(), "OnceSmallSet accessed reentrantly");
+        self.in_use.set(true);
+        let _in_use = InUse(&self.in_use);
+        // Safety: `in_use` guarantees this is the only live reference to the
+        // storage. References handed out point into the boxes, not the
+        // storage itself.
+        f(unsafe { &mut *self.elements.get() })
+    }
+
+    /// Inserts `elem` if not yet present, and returns a reference to the
+    /// element in the set: either `elem` or the equal element already there.
+    pub fn insert(&self, elem: T) -> &T {
+        let ptr: *const T = self.with(|v| match v.iter().position(|e| **e == elem) {
+            Some(i) => &*v[i] as *const T,
+            None => {
+                v.push(Box::new(elem));
+                &**v.last().unwrap() as *const T
+            }
+        });
+        // Safety: the box is never dropped or moved out while `self` is
+        // shared, and its contents do not move when the storage reallocates.
+        unsafe { &*ptr }
+    }
+
+    /// Returns a reference to the element equal to `elem`, if present.
+    pub fn get(&self, elem: &T) -> Option<&T> {
+        let ptr: Option<*const T> =
+            self.with(|v| v.iter().find(|e| ***e == *elem).map(|e| &**e as *const T));
+        // Safety: as in `insert`.
+        ptr.map(|p| unsafe { &*p })
+    }
+
+    /// Tests whether `elem` is present.
+    #[inline]
+    pub fn contains(&self, elem: &T) -> bool {
+        self.get(elem).is_some()
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.with(|v| v.len())
+    }
+
+    /// Returns `true` if the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the elements, in insertion order. Elements
+    /// inserted while iterating are also visited.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        Iter {
+            set: self,
+            index: 0,
+        }
+    }
+
+    /// Removes all elements. This takes `&mut self`, so no references into
+    /// the set can be outstanding.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.elements.get_mut().clear();
+    }
+
+    /// Consumes the set and returns its elements, in insertion order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.elements.into_inner().into_iter().map(|e| *e).collect()
+    }
+
+    fn get_index(&self, index: usize) -> Option<&T> {
+        let ptr: Option<*const T> = self.with(|v| v.get(index).map(|e| &**e as *const T));
+        // Safety: as in `insert`.
+        ptr.map(|p| unsafe { &*p })
+    }
+}
+
+/// An iterator over the elements of a `OnceSmallSet`, in insertion order.
+pub struct Iter<'a, T: 'a, const N: usize> {
+    set: &'a OnceSmallSet<T, N>,
+    index: usize,
+}
+
+impl<'a, T: PartialEq + Eq, const N: usize> Iterator for Iter<'a, T, N> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        let elem = self.set.get_index(self.index);
+        if elem.is_some() {
+            self.index += 1;
+        }
+        elem
+    }
+}
+
+impl<T: PartialEq + Eq, const N: usize> Default for OnceSmallSet<T, N> {
+    #[inline]
+    fn default() -> OnceSmallSet<T, N> {
+        OnceSmallSet::new()
+    }
+}
+
+impl<T: PartialEq + Eq + fmt::Debug, const N: usize> fmt::Debug for OnceSmallSet<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn test_insert_stable() {
+        let s: OnceSmallSet<u32, 2> = OnceSmallSet::new();
+        let first = s.insert(1);
+        // Spill to the heap; `first` must still be valid.
+        for i in 0..16 {
+            s.insert(i);
+        }
+        assert!(*first == 1);
+        assert!(ptr::eq(first, s.get(&1).unwrap()));
+        assert!(s.len() == 16);
+        assert!(s.contains(&15) && !s.contains(&16));
+        assert!(s.iter().copied().take(3).collect::<Vec<u32>>() == vec![1, 0, 2]);
+        assert!(format!("{:?}", s).starts_with("{1, 0, 2"));
+    }
+
+    #[test]
+    fn test_iter_while_inserting() {
+        let s: OnceSmallSet<u32, 4> = OnceSmallSet::new();
+        s.insert(0);
+        let mut seen = vec![];
+        for &e in s.iter() {
+            if e < 3 {
+                s.insert(e + 1);
+            }
+            seen.push(e);
+        }
+        assert!(seen == vec![0, 1, 2, 3]);
+        let mut s = s;
+        s.clear();
+        assert!(s.is_empty());
+        s.insert(7);
+        assert!(s.into_vec() == vec![7]);
+    }
+
+    #[derive(Debug)]
+    struct Reentrant<'a>(u32, Option<&'a OnceSmallSet<Reentrant<'a>, 2>>);
+
+    impl<'a> PartialEq for Reentrant<'a> {
+        fn eq(&self, other: &Reentrant<'a>) -> bool {
+            if let Some(s) = self.1.or(other.1) {
+                s.len();
+            }
+            self.0 == other.0
+        }
+    }
+    impl<'a> Eq for Reentrant<'a> {}
+
+    #[test]
+    #[should_panic(expected = "reentrantly")]
+    fn test_reentrant_panics() {
+        // Leaked, since the set's elements borrow the set itself.
+        let s: &'static OnceSmallSet<Reentrant, 2> = Box::leak(Box::new(OnceSmallSet::new()));
+        s.insert(Reentrant(0, None));
+        s.insert(Reentrant(1, Some(s)));
+    }
+}
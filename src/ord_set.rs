@@ -0,0 +1,358 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A small sorted set.
+
+use smallvec::{Array, SmallVec};
+use std::collections::btree_set;
+use std::collections::BTreeSet;
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+use std::slice;
+
+/// A `SmallOrdSet` is the ordered counterpart of `SmallSet` for `Ord`
+/// elements. While the elements fit in the inline array `A`, they are kept
+/// sorted there and found by binary search. Once the array is full, the set
+/// spills into a `BTreeSet`. Either way, iteration is in ascending order and
+/// `range` queries are supported.
+///
+/// ```
+/// use smallset::SmallOrdSet;
+///
+/// let mut s: SmallOrdSet<[u32; 4]> = SmallOrdSet::new();
+/// s.insert(5);
+/// s.insert(1);
+/// s.insert(3);
+/// assert!(s.iter().copied().collect::<Vec<u32>>() == vec![1, 3, 5]);
+/// assert!(s.range(2..).copied().collect::<Vec<u32>>() == vec![3, 5]);
+/// ```
+pub struct SmallOrdSet<A: Array> {
+    inner: Inner<A>,
+}
+
+enum Inner<A: Array> {
+    /// Sorted, with at most `A::size()` elements.
+    Inline(SmallVec<A>),
+    Heap(BTreeSet<A::Item>),
+}
+
+impl<A: Array> SmallOrdSet<A>
+where
+    A::Item: Ord,
+{
+    /// Creates a new, empty `SmallOrdSet`.
+    #[inline]
+    pub fn new() -> SmallOrdSet<A> {
+        SmallOrdSet {
+            inner: Inner::Inline(SmallVec::new()),
+        }
+    }
+
+    /// Inserts `elem` if not yet present. Returns `true` if the element was
+    /// inserted. Inserting into a full inline array moves the set into a
+    /// `BTreeSet`.
+    pub fn insert(&mut self, elem: A::Item) -> bool {
+        let spilled = match self.inner {
+            Inner::Inline(ref mut v) => match v.binary_search(&elem) {
+                Ok(_) => return false,
+                Err(i) if v.len() < v.inline_size() => {
+                    v.insert(i, elem);
+                    return true;
+                }
+                Err(_) => {
+                    let mut set: BTreeSet<A::Item> = v.drain(..).collect();
+                    set.insert(elem);
+                    set
+                }
+            },
+            Inner::Heap(ref mut set) => return set.insert(elem),
+        };
+        self.inner = Inner::Heap(spilled);
+        true
+    }
+
+    /// Removes `elem`. Returns `true` if the element was removed. A spilled
+    /// set stays in its `BTreeSet` until it is cleared.
+    pub fn remove(&mut self, elem: &A::Item) -> bool {
+        match self.inner {
+            Inner::Inline(ref mut v) => match v.binary_search(elem) {
+                Ok(i) => {
+                    v.remove(i);
+                    true
+                }
+                Err(_) => false,
+            },
+            Inner::Heap(ref mut set) => set.remove(elem),
+        }
+    }
+
+    /// Tests whether `elem` is present.
+    #[inline]
+    pub fn contains(&self, elem: &A::Item) -> bool {
+        match self.inner {
+            Inner::Inline(ref v) => v.binary_search(elem).is_ok(),
+            Inner::Heap(ref set) => set.contains(elem),
+        }
+    }
+
+    /// Returns the smallest element, or `None` if the set is empty.
+    #[inline]
+    pub fn first(&self) -> Option<&A::Item> {
+        self.iter().next()
+    }
+
+    /// Returns the largest element, or `None` if the set is empty.
+    #[inline]
+    pub fn last(&self) -> Option<&A::Item> {
+        self.iter().next_back()
+    }
+
+    /// Returns an iterator over the elements, in ascending order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, A::Item> {
+        Iter {
+            inner: match self.inner {
+                Inner::Inline(ref v) => IterInner::Inline(v.iter()),
+                Inner::Heap(ref set) => IterInner::Heap(set.iter()),
+            },
+        }
+    }
+
+    /// Returns an iterator over the elements within `range`, in ascending
+    /// order. Panics, like `BTreeSet::range`, if the range starts after it
+    /// ends, or starts and ends at the same excluded value.
+    pub fn range<R: RangeBounds<A::Item>>(&self, range: R) -> Iter<'_, A::Item> {
+        match self.inner {
+            Inner::Inline(ref v) => {
+                check_range(&range);
+                let start = match range.start_bound() {
+                    Bound::Included(s) => v.partition_point(|e| e < s),
+                    Bound::Excluded(s) => v.partition_point(|e| e <= s),
+                    Bound::Unbounded => 0,
+                };
+                let end = match range.end_bound() {
+                    Bound::Included(e) => v.partition_point(|x| x <= e),
+                    Bound::Excluded(e) => v.partition_point(|x| x < e),
+                    Bound::Unbounded => v.len(),
+                };
+                Iter {
+                    inner: IterInner::Inline(v[start..end].iter()),
+                }
+            }
+            Inner::Heap(ref set) => Iter {
+                inner: IterInner::Range(set.range(range)),
+            },
+        }
+    }
+
+    /// Returns the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self.inner {
+            Inner::Inline(ref v) => v.len(),
+            Inner::Heap(ref set) => set.len(),
+        }
+    }
+
+    /// Returns `true` if the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the set has moved into a `BTreeSet`.
+    #[inline]
+    pub fn spilled(&self) -> bool {
+        match self.inner {
+            Inner::Inline(_) => false,
+            Inner::Heap(_) => true,
+        }
+    }
+
+    /// Clears the set, returning it to inline storage.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.inner = Inner::Inline(SmallVec::new());
+    }
+}
+
+/// Panics on the same ranges as `BTreeSet::range`, so that inline and spilled
+/// sets behave alike.
+fn check_range<T: Ord, R: RangeBounds<T>>(range: &R) {
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Excluded(s), Bound::Excluded(e)) if s == e => {
+            panic!("range start and end are equal and excluded in SmallOrdSet")
+        }
+        (Bound::Included(s), Bound::Included(e))
+        | (Bound::Included(s), Bound::Excluded(e))
+        | (Bound::Excluded(s), Bound::Included(e))
+        | (Bound::Excluded(s), Bound::Excluded(e))
+            if s > e =>
+        {
+            panic!("range start is greater than range end in SmallOrdSet")
+        }
+        _ => {}
+    }
+}
+
+/// An iterator over the elements of a `SmallOrdSet`, in ascending order.
+pub struct Iter<'a, T: 'a> {
+    inner: IterInner<'a, T>,
+}
+
+enum IterInner<'a, T: 'a> {
+    Inline(slice::Iter<'a, T>),
+    Heap(btree_set::Iter<'a, T>),
+    Range(btree_set::Range<'a, T>),
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        match self.inner {
+            IterInner::Inline(ref mut it) => it.next(),
+            IterInner::Heap(ref mut it) => it.next(),
+            IterInner::Range(ref mut it) => it.next(),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.inner {
+            IterInner::Inline(ref it) => it.size_hint(),
+            IterInner::Heap(ref it) => it.size_hint(),
+            IterInner::Range(ref it) => it.size_hint(),
+        }
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<&'a T> {
+        match self.inner {
+            IterInner::Inline(ref mut it) => it.next_back(),
+            IterInner::Heap(ref mut it) => it.next_back(),
+            IterInner::Range(ref mut it) => it.next_back(),
+        }
+    }
+}
+
+impl<A: Array> Default for SmallOrdSet<A>
+where
+    A::Item: Ord,
+{
+    #[inline]
+    fn default() -> SmallOrdSet<A> {
+        SmallOrdSet::new()
+    }
+}
+
+impl<A: Array> Clone for SmallOrdSet<A>
+where
+    A::Item: Clone,
+{
+    fn clone(&self) -> SmallOrdSet<A> {
+        SmallOrdSet {
+            inner: match self.inner {
+                Inner::Inline(ref v) => Inner::Inline(v.clone()),
+                Inner::Heap(ref set) => Inner::Heap(set.clone()),
+            },
+        }
+    }
+}
+
+impl<A: Array> PartialEq for SmallOrdSet<A>
+where
+    A::Item: Ord,
+{
+    fn eq(&self, other: &SmallOrdSet<A>) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<A: Array> Eq for SmallOrdSet<A> where A::Item: Ord {}
+
+impl<A: Array> fmt::Debug for SmallOrdSet<A>
+where
+    A::Item: Ord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<A: Array> FromIterator<A::Item> for SmallOrdSet<A>
+where
+    A::Item: Ord,
+{
+    fn from_iter<T>(iter: T) -> Self
+    where
+        T: IntoIterator<Item = A::Item>,
+    {
+        let mut set = SmallOrdSet::new();
+        for elem in iter {
+            set.insert(elem);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::vec::Vec;
+
+    fn v(it: Iter<u32>) -> Vec<u32> {
+        it.copied().collect()
+    }
+
+    #[test]
+    fn test_sorted_spill() {
+        let mut s: SmallOrdSet<[u32; 3]> = SmallOrdSet::new();
+        assert!(s.insert(5) && s.insert(1) && s.insert(3));
+        assert!(!s.insert(3));
+        assert!(!s.spilled() && v(s.iter()) == vec![1, 3, 5]);
+        assert!(s.insert(2));
+        assert!(s.spilled() && v(s.iter()) == vec![1, 2, 3, 5]);
+        assert!(s.contains(&2) && !s.contains(&4));
+        assert!(s.remove(&1) && !s.remove(&1));
+        assert!(s.first() == Some(&2) && s.last() == Some(&5));
+        assert!(format!("{:?}", s) == "{2, 3, 5}");
+        s.clear();
+        assert!(s.is_empty() && !s.spilled());
+    }
+
+    #[test]
+    fn test_range() {
+        let inline: SmallOrdSet<[u32; 8]> = vec![7, 1, 5, 3].into_iter().collect();
+        let heap: SmallOrdSet<[u32; 2]> = vec![7, 1, 5, 3].into_iter().collect();
+        assert!(!inline.spilled() && heap.spilled());
+        for s in [v(inline.range(..)), v(heap.range(..))] {
+            assert!(s == vec![1, 3, 5, 7]);
+        }
+        for s in [v(inline.range(3..7)), v(heap.range(3..7))] {
+            assert!(s == vec![3, 5]);
+        }
+        for s in [v(inline.range(2..=7)), v(heap.range(2..=7))] {
+            assert!(s == vec![3, 5, 7]);
+        }
+        let r = (Bound::Excluded(3), Bound::Unbounded);
+        assert!(v(inline.range(r)) == vec![5, 7] && v(heap.range(r)) == vec![5, 7]);
+        assert!(inline.range(4..4).next().is_none());
+        assert!(inline.range(..4).rev().copied().collect::<Vec<u32>>() == vec![3, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "greater than range end")]
+    fn test_range_panics_inline() {
+        let s: SmallOrdSet<[u32; 4]> = vec![1, 2].into_iter().collect();
+        #[allow(clippy::reversed_empty_ranges)]
+        s.range(3..1);
+    }
+}
@@ -0,0 +1,169 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A pool of heap buffers recycled between spilled sets.
+
+use smallvec::{Array, SmallVec};
+use std::mem;
+use std::vec::Vec;
+
+use SmallSet;
+
+/// A `SpillPool` holds heap buffers freed by spilled sets so that later
+/// spills can reuse them instead of calling the allocator. For bursty
+/// workloads, where many sets briefly spill and are then cleared, this takes
+/// malloc and free of spill storage off the hot path.
+///
+/// The pool is opt-in and passed explicitly: buffers are returned with
+/// `SmallSet::clear_into_pool` or `SmallSet::recycle_into`, and reused by
+/// `SmallSet::insert_pooled`. A per-thread pool can be kept in a
+/// `thread_local!` `RefCell`. At most `max_buffers` buffers are kept; extra
+/// ones are freed.
+///
+/// ```
+/// use smallset::{SmallSet, SpillPool};
+///
+/// let mut pool = SpillPool::new(4);
+/// let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+/// for round in 0..3 {
+///     for i in 0..5 {
+///         s.insert_pooled(round * 10 + i, &mut pool);
+///     }
+///     s.clear_into_pool(&mut pool);
+/// }
+/// assert!(pool.len() == 1);
+/// ```
+pub struct SpillPool<T> {
+    buffers: Vec<Vec<T>>,
+    max_buffers: usize,
+}
+
+impl<T> SpillPool<T> {
+    /// Creates an empty pool that keeps at most `max_buffers` buffers.
+    #[inline]
+    pub fn new(max_buffers: usize) -> SpillPool<T> {
+        SpillPool {
+            buffers: Vec::new(),
+            max_buffers,
+        }
+    }
+
+    /// Returns the number of buffers in the pool.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Returns `true` if the pool holds no buffers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Frees all buffers in the pool.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.buffers.clear();
+    }
+
+    /// Takes a buffer with room for more than `inline_size` elements, if the
+    /// pool has one.
+    fn take(&mut self, inline_size: usize) -> Option<Vec<T>> {
+        let i = self
+            .buffers
+            .iter()
+            .position(|b| b.capacity() > inline_size)?;
+        Some(self.buffers.swap_remove(i))
+    }
+
+    /// Empties `buffer` and keeps it, unless the pool is full.
+    fn give(&mut self, mut buffer: Vec<T>) {
+        buffer.clear();
+        if self.buffers.len() < self.max_buffers {
+            self.buffers.push(buffer);
+        }
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    /// Inserts `elem` if not yet present, like `insert`. If this insert spills
+    /// the set to the heap, a buffer is taken from `pool` when one is
+    /// available, and otherwise allocated as usual. Returns `true` if the
+    /// element was inserted.
+    pub fn insert_pooled(&mut self, elem: A::Item, pool: &mut SpillPool<A::Item>) -> bool {
+        if self.contains(&elem) {
+            return false;
+        }
+        if self.will_spill_on_insert() {
+            if let Some(mut buffer) = pool.take(self.elements.inline_size()) {
+                buffer.extend(self.elements.drain(..));
+                buffer.push(elem);
+                self.elements = SmallVec::from_vec(buffer);
+                return true;
+            }
+        }
+        self.elements.push(elem);
+        true
+    }
+
+    /// Clears the set. If it had spilled, its heap buffer is handed to `pool`
+    /// and the set goes back to inline storage.
+    pub fn clear_into_pool(&mut self, pool: &mut SpillPool<A::Item>) {
+        if self.elements.spilled() {
+            pool.give(mem::replace(&mut self.elements, SmallVec::new()).into_vec());
+        } else {
+            self.elements.clear();
+        }
+    }
+
+    /// Drops the set, handing its heap buffer to `pool` if it had spilled.
+    #[inline]
+    pub fn recycle_into(mut self, pool: &mut SpillPool<A::Item>) {
+        self.clear_into_pool(pool);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reuse() {
+        let mut pool = SpillPool::new(1);
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        for i in 0..8 {
+            s.insert_pooled(i, &mut pool);
+        }
+        assert!(!s.insert_pooled(3, &mut pool));
+        let ptr = s.elements.as_ptr();
+        s.clear_into_pool(&mut pool);
+        assert!(s.is_empty() && !s.elements.spilled());
+        assert!(pool.len() == 1);
+
+        // The next spill reuses the pooled buffer.
+        let mut t: SmallSet<[u32; 2]> = SmallSet::new();
+        for i in 0..3 {
+            t.insert_pooled(i, &mut pool);
+        }
+        assert!(t.elements.spilled() && t.elements.as_ptr() == ptr);
+        assert!(t.iter().copied().collect::<Vec<u32>>() == vec![0, 1, 2]);
+        assert!(pool.is_empty());
+
+        // The pool keeps at most one buffer; the second is freed.
+        let mut u: SmallSet<[u32; 2]> = (0..3).collect();
+        t.recycle_into(&mut pool);
+        u.clear_into_pool(&mut pool);
+        assert!(pool.len() == 1);
+        // An inline set has no buffer to give back.
+        let v: SmallSet<[u32; 2]> = (0..2).collect();
+        pool.clear();
+        v.recycle_into(&mut pool);
+        assert!(pool.is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A small set keyed by pointer identity rather than by value.
+
+use smallvec::{Array, SmallVec};
+use std::fmt;
+use std::ops::Deref;
+use std::slice::Iter;
+
+/// A `PtrSmallSet` is a small set of pointers (`&T`, `Rc<T>`, `Arc<T>`,
+/// `Box<T>`, ...) in which two elements are the same if they point to the
+/// same object. The pointees are never compared, so `T` need not implement
+/// `PartialEq`, and two distinct objects with equal contents are both kept.
+/// This is the usual "visited nodes" set for graph traversals.
+///
+/// Like `SmallSet`, elements are kept in insertion order in a `SmallVec`, and
+/// operations perform a linear scan. Note that distinct zero-sized values may
+/// share an address and will then be treated as the same element.
+///
+/// ```
+/// use smallset::PtrSmallSet;
+/// use std::rc::Rc;
+///
+/// let a = Rc::new(1);
+/// let b = Rc::new(1);
+/// let mut visited: PtrSmallSet<[Rc<u32>; 4]> = PtrSmallSet::new();
+/// assert!(visited.insert(a.clone()));
+/// assert!(visited.insert(b.clone()));
+/// assert!(!visited.insert(a.clone()));
+/// assert!(visited.contains(&*b));
+/// ```
+pub struct PtrSmallSet<A: Array> {
+    elements: SmallVec<A>,
+}
+
+/// Returns the address `p` points to, ignoring any pointer metadata.
+#[inline]
+fn addr<P: Deref + ?Sized>(p: &P) -> *const () {
+    addr_of(&**p)
+}
+
+#[inline]
+fn addr_of<T: ?Sized>(t: &T) -> *const () {
+    t as *const T as *const ()
+}
+
+impl<A: Array> PtrSmallSet<A>
+where
+    A::Item: Deref,
+{
+    /// Creates a new, empty `PtrSmallSet`.
+    #[inline]
+    pub fn new() -> PtrSmallSet<A> {
+        PtrSmallSet {
+            elements: SmallVec::new(),
+        }
+    }
+
+    /// Inserts `elem` into the set if no element pointing to the same object
+    /// is present. Returns `true` if the element was inserted.
+    #[inline]
+    pub fn insert(&mut self, elem: A::Item) -> bool {
+        if self.position(addr(&elem)).is_some() {
+            false
+        } else {
+            self.elements.push(elem);
+            true
+        }
+    }
+
+    /// Removes the element pointing to `target`, returning it, or `None` if
+    /// there is no such element.
+    #[inline]
+    pub fn remove(&mut self, target: &<A::Item as Deref>::Target) -> Option<A::Item> {
+        self.position(addr_of(target))
+            .map(|pos| self.elements.remove(pos))
+    }
+
+    /// Tests whether an element pointing to `target` is present.
+    #[inline]
+    pub fn contains(&self, target: &<A::Item as Deref>::Target) -> bool {
+        self.position(addr_of(target)).is_some()
+    }
+
+    /// Returns an iterator over the set elements, in insertion order.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, A::Item> {
+        self.elements.iter()
+    }
+
+    /// Returns the current length of the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    /// Clears the set.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.elements.clear();
+    }
+
+    fn position(&self, target: *const ()) -> Option<usize> {
+        self.elements.iter().position(|e| addr(e) == target)
+    }
+}
+
+impl<A: Array> Default for PtrSmallSet<A>
+where
+    A::Item: Deref,
+{
+    #[inline]
+    fn default() -> PtrSmallSet<A> {
+        PtrSmallSet::new()
+    }
+}
+
+impl<A: Array> Clone for PtrSmallSet<A>
+where
+    A::Item: Deref + Clone,
+{
+    fn clone(&self) -> PtrSmallSet<A> {
+        PtrSmallSet {
+            elements: self.elements.clone(),
+        }
+    }
+}
+
+impl<A: Array> fmt::Debug for PtrSmallSet<A>
+where
+    A::Item: Deref + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.elements.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+    use std::string::String;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_identity() {
+        let nodes = [String::from("x"), String::from("x"), String::from("y")];
+        let mut s: PtrSmallSet<[&String; 2]> = PtrSmallSet::new();
+        assert!(s.insert(&nodes[0]));
+        assert!(s.insert(&nodes[1]));
+        assert!(!s.insert(&nodes[0]));
+        assert!(s.len() == 2);
+        assert!(s.contains(&nodes[1]));
+        assert!(!s.contains(&nodes[2]));
+        assert!(s.remove(&nodes[0]).is_some());
+        assert!(s.remove(&nodes[0]).is_none());
+        assert!(!s.contains(&nodes[0]));
+    }
+
+    #[test]
+    fn test_unsized_rc() {
+        let a: Rc<str> = Rc::from("abc");
+        let b: Rc<str> = Rc::from("abc");
+        let mut s: PtrSmallSet<[Rc<str>; 1]> = PtrSmallSet::new();
+        assert!(s.insert(a.clone()));
+        assert!(s.insert(b.clone()));
+        assert!(!s.insert(a.clone()));
+        assert!(s.iter().map(|r| &**r).collect::<Vec<&str>>() == vec!["abc", "abc"]);
+        assert!(Rc::ptr_eq(&s.remove(&*b).unwrap(), &b));
+    }
+}
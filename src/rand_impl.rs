@@ -0,0 +1,107 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! `rand` support, enabled with the `rand` feature.
+
+use rand::distr::Distribution;
+use rand::seq::index;
+use rand::Rng;
+use smallvec::Array;
+
+use SmallSet;
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    /// Draws elements from `dist` until the set holds `k` distinct elements,
+    /// for generating test data. Returns `None` if `k` distinct elements have
+    /// not been found after `max_draws` draws, which bounds the running time
+    /// when `dist` has fewer than `k` likely values.
+    pub fn random_from_distribution<R, D>(
+        rng: &mut R,
+        dist: D,
+        k: usize,
+        max_draws: usize,
+    ) -> Option<SmallSet<A>>
+    where
+        R: Rng + ?Sized,
+        D: Distribution<A::Item>,
+    {
+        let mut set = SmallSet::new();
+        set.reserve(k);
+        for _ in 0..max_draws {
+            if set.len() == k {
+                break;
+            }
+            set.insert(dist.sample(rng));
+        }
+        if set.len() == k {
+            Some(set)
+        } else {
+            None
+        }
+    }
+}
+
+impl<A: Array> SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Clone,
+{
+    /// Returns a set of `k` elements chosen uniformly at random from
+    /// `universe`, in random order, or `None` if `universe` has fewer than `k`
+    /// elements. The elements of `universe` are expected to be distinct; if
+    /// they are not, the set may end up with fewer than `k` elements.
+    pub fn random_from_slice<R>(rng: &mut R, universe: &[A::Item], k: usize) -> Option<SmallSet<A>>
+    where
+        R: Rng + ?Sized,
+    {
+        if k > universe.len() {
+            return None;
+        }
+        Some(
+            index::sample(rng, universe.len(), k)
+                .into_iter()
+                .map(|i| universe[i].clone())
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate rand_xorshift;
+
+    use self::rand_xorshift::XorShiftRng;
+    use super::*;
+    use rand::distr::Uniform;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_random_from_distribution() {
+        let mut rng = XorShiftRng::seed_from_u64(1);
+        let dist = Uniform::new(0u32, 10).unwrap();
+        let s: SmallSet<[u32; 4]> =
+            SmallSet::random_from_distribution(&mut rng, dist, 6, 1000).unwrap();
+        assert!(s.len() == 6 && s.iter().all(|e| *e < 10));
+        let dist = Uniform::new(0u32, 3).unwrap();
+        let s: Option<SmallSet<[u32; 4]>> =
+            SmallSet::random_from_distribution(&mut rng, dist, 4, 1000);
+        assert!(s.is_none());
+    }
+
+    #[test]
+    fn test_random_from_slice() {
+        let mut rng = XorShiftRng::seed_from_u64(2);
+        let universe: Vec<u32> = (100..120).collect();
+        for k in 0..=20 {
+            let s: SmallSet<[u32; 4]> =
+                SmallSet::random_from_slice(&mut rng, &universe, k).unwrap();
+            assert!(s.len() == k && s.iter().all(|e| universe.contains(e)));
+        }
+        assert!(SmallSet::<[u32; 4]>::random_from_slice(&mut rng, &universe, 21).is_none());
+    }
+}
@@ -0,0 +1,170 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! `serde` support, enabled with the `serde` feature.
+
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use smallvec::Array;
+use std::fmt;
+use std::marker::PhantomData;
+use std::vec::Vec;
+
+use SmallSet;
+
+/// Serializes the set as a sequence in its iteration order.
+impl<A: Array> Serialize for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Deserializes the set from a sequence. Repeated elements are kept once, at
+/// the position of their first occurrence. Elements may borrow from the
+/// input, so a `SmallSet<[&'de str; N]>` can be deserialized without
+/// allocating a `String` per element.
+impl<'de, A: Array> Deserialize<'de> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<SmallSet<A>, D::Error> {
+        struct SeqVisitor<A>(PhantomData<A>);
+
+        impl<'de, A: Array> Visitor<'de> for SeqVisitor<A>
+        where
+            A::Item: PartialEq + Eq + Deserialize<'de>,
+        {
+            type Value = SmallSet<A>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<SmallSet<A>, S::Error> {
+                let mut set = SmallSet::new();
+                while let Some(elem) = seq.next_element()? {
+                    set.insert(elem);
+                }
+                Ok(set)
+            }
+        }
+
+        deserializer.deserialize_seq(SeqVisitor(PhantomData))
+    }
+
+    /// Clears `place` and fills it from the sequence, reusing its existing
+    /// inline or heap storage. If deserialization fails, `place` holds the
+    /// elements read before the failure.
+    fn deserialize_in_place<D: Deserializer<'de>>(
+        deserializer: D,
+        place: &mut SmallSet<A>,
+    ) -> Result<(), D::Error> {
+        struct InPlaceVisitor<'a, A: Array + 'a>(&'a mut SmallSet<A>);
+
+        impl<'a, 'de, A: Array> Visitor<'de> for InPlaceVisitor<'a, A>
+        where
+            A::Item: PartialEq + Eq + Deserialize<'de>,
+        {
+            type Value = ();
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<(), S::Error> {
+                self.0.clear();
+                while let Some(elem) = seq.next_element()? {
+                    self.0.insert(elem);
+                }
+                Ok(())
+            }
+        }
+
+        deserializer.deserialize_seq(InPlaceVisitor(place))
+    }
+}
+
+/// Serializes `set` as a sequence in ascending element order, rather than in
+/// iteration order. Two equal sets always produce the same output this way,
+/// regardless of the order in which their elements were inserted. Intended
+/// for use with `#[serde(serialize_with = "smallset::serialize_sorted")]`.
+pub fn serialize_sorted<A, S>(set: &SmallSet<A>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    A: Array,
+    A::Item: PartialEq + Eq + Ord + Serialize,
+    S: Serializer,
+{
+    let mut elems: Vec<&A::Item> = set.iter().collect();
+    elems.sort();
+    let mut seq = serializer.serialize_seq(Some(elems.len()))?;
+    for elem in elems {
+        seq.serialize_element(elem)?;
+    }
+    seq.end()
+}
+
+#[cfg(test)]
+mod test {
+    extern crate serde_json;
+
+    use super::*;
+
+    #[test]
+    fn test_serialize() {
+        let mut s: SmallSet<[u32; 2]> = SmallSet::new();
+        s.insert(3);
+        s.insert(1);
+        s.insert(2);
+        assert!(serde_json::to_string(&s).unwrap() == "[3,1,2]");
+    }
+
+    fn to_sorted_json(s: &SmallSet<[u32; 2]>) -> String {
+        let mut buf = Vec::new();
+        serialize_sorted(s, &mut serde_json::Serializer::new(&mut buf)).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn test_serialize_sorted() {
+        let a: SmallSet<[u32; 2]> = vec![3, 1, 2].into_iter().collect();
+        let b: SmallSet<[u32; 2]> = vec![2, 3, 1].into_iter().collect();
+        assert!(to_sorted_json(&a) == "[1,2,3]");
+        assert!(to_sorted_json(&b) == "[1,2,3]");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let s: SmallSet<[u32; 2]> = serde_json::from_str("[3,1,3,2]").unwrap();
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![3, 1, 2]);
+        assert!(serde_json::from_str::<SmallSet<[u32; 2]>>("{}").is_err());
+    }
+
+    #[test]
+    fn test_deserialize_borrowed() {
+        let input = String::from(r#"["red","green","red"]"#);
+        let s: SmallSet<[&str; 4]> = serde_json::from_str(&input).unwrap();
+        assert!(s.as_slice() == ["red", "green"]);
+        let start = input.as_ptr() as usize;
+        assert!(s
+            .iter()
+            .all(|e| (e.as_ptr() as usize).wrapping_sub(start) < input.len()));
+        // Strings with escapes cannot be borrowed, and are rejected.
+        assert!(serde_json::from_str::<SmallSet<[&str; 4]>>(r#"["a\nb"]"#).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_in_place() {
+        let mut s: SmallSet<[u32; 2]> = (0..10).collect();
+        let cap = s.elements.capacity();
+        let mut de = serde_json::Deserializer::from_str("[7,8,7,9]");
+        SmallSet::deserialize_in_place(&mut de, &mut s).unwrap();
+        assert!(s.iter().copied().collect::<Vec<u32>>() == vec![7, 8, 9]);
+        assert!(s.elements.spilled() && s.elements.capacity() == cap);
+    }
+}
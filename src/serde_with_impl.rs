@@ -0,0 +1,109 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! `serde_with` adapters, enabled with the `serde_with` feature.
+//!
+//! These mirror the adapters `serde_with` provides for `HashSet`, so a field
+//! of type `SmallSet<[T; N]>` can be annotated with, e.g.,
+//! `#[serde_as(as = "SmallSet<[DisplayFromStr; N]>")]`. The inline size of
+//! the adapter type does not need to match the field's. A `SmallSet` field can
+//! also be used with the adapters that work on any collection, such as
+//! `StringWithSeparator`.
+
+use serde::de::{Deserializer, SeqAccess, Visitor};
+use serde::ser::Serializer;
+use serde_with::de::DeserializeAsWrap;
+use serde_with::ser::SerializeAsWrap;
+use serde_with::{DeserializeAs, SerializeAs};
+use smallvec::Array;
+use std::fmt;
+use std::marker::PhantomData;
+
+use SmallSet;
+
+impl<A, B> SerializeAs<SmallSet<A>> for SmallSet<B>
+where
+    A: Array,
+    A::Item: PartialEq + Eq,
+    B: Array,
+    B::Item: SerializeAs<A::Item>,
+{
+    fn serialize_as<S: Serializer>(source: &SmallSet<A>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(source.iter().map(SerializeAsWrap::<A::Item, B::Item>::new))
+    }
+}
+
+impl<'de, A, B> DeserializeAs<'de, SmallSet<A>> for SmallSet<B>
+where
+    A: Array,
+    A::Item: PartialEq + Eq,
+    B: Array,
+    B::Item: DeserializeAs<'de, A::Item>,
+{
+    fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<SmallSet<A>, D::Error> {
+        struct SeqVisitor<A, U>(PhantomData<(A, U)>);
+
+        impl<'de, A, U> Visitor<'de> for SeqVisitor<A, U>
+        where
+            A: Array,
+            A::Item: PartialEq + Eq,
+            U: DeserializeAs<'de, A::Item>,
+        {
+            type Value = SmallSet<A>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<SmallSet<A>, S::Error> {
+                let mut set = SmallSet::new();
+                while let Some(elem) = seq.next_element::<DeserializeAsWrap<A::Item, U>>()? {
+                    set.insert(elem.into_inner());
+                }
+                Ok(set)
+            }
+        }
+
+        deserializer.deserialize_seq(SeqVisitor::<A, B::Item>(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate serde_json;
+
+    use super::*;
+    use serde_with::formats::CommaSeparator;
+    use serde_with::{DisplayFromStr, StringWithSeparator};
+
+    fn to_json<U: SerializeAs<SmallSet<[u32; 2]>>>(s: &SmallSet<[u32; 2]>) -> String {
+        let mut buf = Vec::new();
+        U::serialize_as(s, &mut serde_json::Serializer::new(&mut buf)).unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    fn from_json<U: for<'de> DeserializeAs<'de, SmallSet<[u32; 2]>>>(
+        json: &str,
+    ) -> SmallSet<[u32; 2]> {
+        U::deserialize_as(&mut serde_json::Deserializer::from_str(json)).unwrap()
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        let s: SmallSet<[u32; 2]> = vec![3, 1, 2].into_iter().collect();
+        assert!(to_json::<SmallSet<[DisplayFromStr; 1]>>(&s) == r#"["3","1","2"]"#);
+        let d = from_json::<SmallSet<[DisplayFromStr; 1]>>(r#"["3","1","3","2"]"#);
+        assert!(d.iter().copied().collect::<Vec<u32>>() == vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_string_with_separator() {
+        let s: SmallSet<[u32; 2]> = vec![3, 1, 2].into_iter().collect();
+        assert!(to_json::<StringWithSeparator<CommaSeparator, u32>>(&s) == r#""3,1,2""#);
+        let d = from_json::<StringWithSeparator<CommaSeparator, u32>>(r#""1,2,1""#);
+        assert!(d.iter().copied().collect::<Vec<u32>>() == vec![1, 2]);
+    }
+}
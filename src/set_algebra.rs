@@ -0,0 +1,413 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Set algebra over any mix of `SmallSet`, `HashSet` and `BTreeSet`.
+//!
+//! The functions here take their arguments through the `SetLike` trait, so
+//! sets of different types can be combined without first converting them all
+//! to one type. The k-way functions take a slice of `&dyn SetLike<T>`.
+//! `UnionView` and `IntersectionView` combine sets lazily, without building
+//! the result.
+//!
+#![cfg_attr(feature = "std", doc = "```")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
+//! use smallset::set_algebra::{self, SetLike};
+//! use smallset::SmallSet;
+//! use std::collections::{BTreeSet, HashSet};
+//!
+//! let a: SmallSet<[u32; 4]> = vec![1, 2, 3].into_iter().collect();
+//! let b: HashSet<u32> = vec![2, 3, 4].into_iter().collect();
+//! let c: BTreeSet<u32> = vec![3, 2].into_iter().collect();
+//! let sets: [&dyn SetLike<u32>; 3] = [&a, &b, &c];
+//! let common: Vec<u32> = set_algebra::intersection_all(&sets);
+//! assert!(common == vec![2, 3]);
+//! assert!(set_algebra::is_subset(&c, &a));
+//! ```
+
+use smallvec::Array;
+use std::collections::BTreeSet;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+#[cfg(feature = "std")]
+use std::hash::{BuildHasher, Hash};
+
+use SmallSet;
+
+/// The operations the functions in this module need from a set. The trait is
+/// object safe, so that sets of different types can be passed together.
+pub trait SetLike<T> {
+    /// Tests whether `elem` is present.
+    fn contains(&self, elem: &T) -> bool;
+
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the set is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Calls `f` on each element, in the set's iteration order.
+    fn for_each_elem(&self, f: &mut dyn FnMut(&T));
+
+    /// Calls `f` on each element, in the set's iteration order, until it
+    /// returns `false`. Returns `true` if `f` returned `true` for every
+    /// element. The default implementation goes through `for_each_elem`,
+    /// which cannot stop early, so it skips `f` for the remaining elements
+    /// but still visits them; implementations should override it.
+    fn all_elems(&self, f: &mut dyn FnMut(&T) -> bool) -> bool {
+        let mut all = true;
+        self.for_each_elem(&mut |e| all = all && f(e));
+        all
+    }
+}
+
+impl<A: Array> SetLike<A::Item> for SmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn contains(&self, elem: &A::Item) -> bool {
+        SmallSet::contains(self, elem)
+    }
+
+    fn len(&self) -> usize {
+        SmallSet::len(self)
+    }
+
+    fn for_each_elem(&self, f: &mut dyn FnMut(&A::Item)) {
+        self.iter().for_each(f)
+    }
+
+    fn all_elems(&self, f: &mut dyn FnMut(&A::Item) -> bool) -> bool {
+        self.iter().all(f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Hash + Eq, S: BuildHasher> SetLike<T> for HashSet<T, S> {
+    fn contains(&self, elem: &T) -> bool {
+        HashSet::contains(self, elem)
+    }
+
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+
+    fn for_each_elem(&self, f: &mut dyn FnMut(&T)) {
+        self.iter().for_each(f)
+    }
+
+    fn all_elems(&self, f: &mut dyn FnMut(&T) -> bool) -> bool {
+        self.iter().all(f)
+    }
+}
+
+impl<T: Ord> SetLike<T> for BTreeSet<T> {
+    fn contains(&self, elem: &T) -> bool {
+        BTreeSet::contains(self, elem)
+    }
+
+    fn len(&self) -> usize {
+        BTreeSet::len(self)
+    }
+
+    fn for_each_elem(&self, f: &mut dyn FnMut(&T)) {
+        self.iter().for_each(f)
+    }
+
+    fn all_elems(&self, f: &mut dyn FnMut(&T) -> bool) -> bool {
+        self.iter().all(f)
+    }
+}
+
+/// Returns `true` if every element of `a` is in `b`.
+pub fn is_subset<T, A, B>(a: &A, b: &B) -> bool
+where
+    A: SetLike<T> + ?Sized,
+    B: SetLike<T> + ?Sized,
+{
+    if a.len() > b.len() {
+        return false;
+    }
+    a.all_elems(&mut |e| b.contains(e))
+}
+
+/// Returns `true` if `a` and `b` have no elements in common.
+pub fn is_disjoint<T, A, B>(a: &A, b: &B) -> bool
+where
+    A: SetLike<T> + ?Sized,
+    B: SetLike<T> + ?Sized,
+{
+    a.all_elems(&mut |e| !b.contains(e))
+}
+
+/// Returns the number of elements that are in both `a` and `b`.
+pub fn intersection_len<T, A, B>(a: &A, b: &B) -> usize
+where
+    A: SetLike<T> + ?Sized,
+    B: SetLike<T> + ?Sized,
+{
+    let mut n = 0;
+    a.for_each_elem(&mut |e| {
+        if b.contains(e) {
+            n += 1;
+        }
+    });
+    n
+}
+
+/// Returns the Jaccard similarity of `a` and `b`: the size of their
+/// intersection divided by the size of their union. Two empty sets have a
+/// similarity of `1.0`.
+pub fn jaccard<T, A, B>(a: &A, b: &B) -> f64
+where
+    A: SetLike<T> + ?Sized,
+    B: SetLike<T> + ?Sized,
+{
+    let common = intersection_len(a, b);
+    let union = a.len() + b.len() - common;
+    if union == 0 {
+        1.0
+    } else {
+        common as f64 / union as f64
+    }
+}
+
+/// Returns the union of all of `sets`, containing each element once. Elements
+/// are produced in order of the first set that contains them.
+pub fn union_all<T, O>(sets: &[&dyn SetLike<T>]) -> O
+where
+    T: Clone,
+    O: Default + Extend<T>,
+{
+    let mut out = O::default();
+    for (i, set) in sets.iter().enumerate() {
+        set.for_each_elem(&mut |e| {
+            if !sets[..i].iter().any(|s| s.contains(e)) {
+                out.extend(Some(e.clone()));
+            }
+        });
+    }
+    out
+}
+
+/// Returns the elements that are in every one of `sets`, in the iteration
+/// order of the smallest set. The intersection of no sets is empty.
+pub fn intersection_all<T, O>(sets: &[&dyn SetLike<T>]) -> O
+where
+    T: Clone,
+    O: Default + Extend<T>,
+{
+    let mut out = O::default();
+    let smallest = match sets.iter().min_by_key(|s| s.len()) {
+        Some(s) => s,
+        None => return out,
+    };
+    smallest.for_each_elem(&mut |e| {
+        if sets.iter().all(|s| s.contains(e)) {
+            out.extend(Some(e.clone()));
+        }
+    });
+    out
+}
+
+/// A lazy view of the union of two sets. Queries are answered from the
+/// underlying sets on demand, so building a view never allocates. Views
+/// implement `SetLike` themselves, so they can be nested to combine more than
+/// two sets.
+pub struct UnionView<'a, T: 'a> {
+    a: &'a dyn SetLike<T>,
+    b: &'a dyn SetLike<T>,
+}
+
+impl<'a, T> UnionView<'a, T> {
+    /// Creates a view of the union of `a` and `b`.
+    pub fn new(a: &'a dyn SetLike<T>, b: &'a dyn SetLike<T>) -> UnionView<'a, T> {
+        UnionView { a, b }
+    }
+
+    /// Returns bounds on the number of elements, without scanning: at least
+    /// the size of the larger set and at most the sum of both sizes.
+    pub fn len_bounds(&self) -> (usize, usize) {
+        let (a, b) = (self.a.len(), self.b.len());
+        (a.max(b), a + b)
+    }
+}
+
+impl<'a, T> SetLike<T> for UnionView<'a, T> {
+    fn contains(&self, elem: &T) -> bool {
+        self.a.contains(elem) || self.b.contains(elem)
+    }
+
+    /// Counts the elements exactly, which scans the second set.
+    fn len(&self) -> usize {
+        self.a.len() + self.b.len() - intersection_len(self.b, self.a)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.a.is_empty() && self.b.is_empty()
+    }
+
+    /// Visits the elements of the first set, then those of the second set
+    /// that are not in the first.
+    fn for_each_elem(&self, f: &mut dyn FnMut(&T)) {
+        self.a.for_each_elem(f);
+        let a = self.a;
+        self.b.for_each_elem(&mut |e| {
+            if !a.contains(e) {
+                f(e);
+            }
+        });
+    }
+
+    fn all_elems(&self, f: &mut dyn FnMut(&T) -> bool) -> bool {
+        let a = self.a;
+        self.a.all_elems(f) && self.b.all_elems(&mut |e| a.contains(e) || f(e))
+    }
+}
+
+/// A lazy view of the intersection of two sets. Like `UnionView`, it never
+/// allocates and can be nested.
+pub struct IntersectionView<'a, T: 'a> {
+    a: &'a dyn SetLike<T>,
+    b: &'a dyn SetLike<T>,
+}
+
+impl<'a, T> IntersectionView<'a, T> {
+    /// Creates a view of the intersection of `a` and `b`. Iteration follows
+    /// `a`, so passing the smaller set first makes scans cheaper.
+    pub fn new(a: &'a dyn SetLike<T>, b: &'a dyn SetLike<T>) -> IntersectionView<'a, T> {
+        IntersectionView { a, b }
+    }
+
+    /// Returns bounds on the number of elements, without scanning: at most
+    /// the size of the smaller set.
+    pub fn len_bounds(&self) -> (usize, usize) {
+        (0, self.a.len().min(self.b.len()))
+    }
+}
+
+impl<'a, T> SetLike<T> for IntersectionView<'a, T> {
+    fn contains(&self, elem: &T) -> bool {
+        self.a.contains(elem) && self.b.contains(elem)
+    }
+
+    /// Counts the elements exactly, which scans the first set.
+    fn len(&self) -> usize {
+        intersection_len(self.a, self.b)
+    }
+
+    /// Visits the elements of the first set that are also in the second.
+    fn for_each_elem(&self, f: &mut dyn FnMut(&T)) {
+        let b = self.b;
+        self.a.for_each_elem(&mut |e| {
+            if b.contains(e) {
+                f(e);
+            }
+        });
+    }
+
+    fn all_elems(&self, f: &mut dyn FnMut(&T) -> bool) -> bool {
+        let b = self.b;
+        self.a.all_elems(&mut |e| !b.contains(e) || f(e))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Counts `contains` calls, and leaves `all_elems` to the default.
+    struct Counting<'a> {
+        set: &'a HashSet<u32>,
+        calls: Cell<usize>,
+    }
+
+    impl<'a> SetLike<u32> for Counting<'a> {
+        fn contains(&self, elem: &u32) -> bool {
+            self.calls.set(self.calls.get() + 1);
+            self.set.contains(elem)
+        }
+
+        fn len(&self) -> usize {
+            self.set.len()
+        }
+
+        fn for_each_elem(&self, f: &mut dyn FnMut(&u32)) {
+            self.set.iter().for_each(f)
+        }
+    }
+
+    #[test]
+    fn test_mixed() {
+        let a: SmallSet<[u32; 4]> = vec![1, 2, 3].into_iter().collect();
+        let b: HashSet<u32> = vec![3, 4].into_iter().collect();
+        let c: BTreeSet<u32> = vec![5, 1].into_iter().collect();
+        let sets: [&dyn SetLike<u32>; 3] = [&a, &b, &c];
+
+        let u: Vec<u32> = union_all(&sets);
+        assert!(u[..3] == [1, 2, 3] && u.len() == 5);
+        let i: Vec<u32> = intersection_all(&sets);
+        assert!(i.is_empty());
+        let i: Vec<u32> = intersection_all(&sets[..2]);
+        assert!(i == vec![3]);
+        let i: Vec<u32> = intersection_all::<u32, _>(&[]);
+        assert!(i.is_empty());
+
+        assert!(!is_subset(&a, &b));
+        assert!(is_subset(&BTreeSet::new(), &b));
+        assert!(is_disjoint(&b, &c));
+        assert!(jaccard(&a, &b) == 0.25);
+        assert!(jaccard(&HashSet::<u32>::new(), &BTreeSet::new()) == 1.0);
+    }
+
+    #[test]
+    fn test_views() {
+        let a: SmallSet<[u32; 4]> = vec![1, 2, 3].into_iter().collect();
+        let b: HashSet<u32> = vec![3, 4].into_iter().collect();
+        let c: BTreeSet<u32> = vec![4, 1, 5].into_iter().collect();
+
+        let ab = UnionView::new(&a, &b);
+        assert!(ab.len() == 4 && ab.len_bounds() == (3, 5));
+        assert!(ab.contains(&4) && !ab.contains(&5));
+        let mut elems = Vec::new();
+        ab.for_each_elem(&mut |e| elems.push(*e));
+        assert!(elems == vec![1, 2, 3, 4]);
+
+        let abc = IntersectionView::new(&ab, &c);
+        assert!(abc.len() == 2 && abc.len_bounds() == (0, 3));
+        assert!(abc.contains(&1) && !abc.contains(&5) && !abc.contains(&2));
+        let mut elems = Vec::new();
+        abc.for_each_elem(&mut |e| elems.push(*e));
+        assert!(elems == vec![1, 4]);
+        assert!(is_subset(&abc, &c));
+    }
+
+    #[test]
+    fn test_short_circuit() {
+        let a: SmallSet<[u32; 4]> = vec![9, 1, 2, 3].into_iter().collect();
+        let h: HashSet<u32> = (0..8).collect();
+        let b = Counting {
+            set: &h,
+            calls: Cell::new(0),
+        };
+        assert!(!is_subset(&a, &b) && b.calls.get() == 1);
+        b.calls.set(0);
+        assert!(!is_disjoint(&a, &b) && b.calls.get() == 2);
+        b.calls.set(0);
+        let u = UnionView::new(&a, &b);
+        assert!(!u.all_elems(&mut |&e| e < 9) && b.calls.get() == 0);
+        assert!(is_subset(&IntersectionView::new(&a, &b), &b));
+        // The default `all_elems` visits everything but stops calling `f`.
+        let mut seen = 0;
+        assert!(!b.all_elems(&mut |_| {
+            seen += 1;
+            false
+        }));
+        assert!(seen == 1 && is_subset(&b, &h) && !is_subset(&b, &a));
+    }
+}
@@ -0,0 +1,179 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! A read-mostly `SmallSet` shared between threads, enabled with the
+//! `arc-swap` feature.
+
+use arc_swap::ArcSwap;
+use smallvec::Array;
+use std::fmt;
+use std::sync::Arc;
+
+use SmallSet;
+
+/// A `SharedSmallSet` is a `SmallSet` that many threads can query and
+/// occasionally update through a shared reference, without a lock.
+///
+/// Readers (`contains`, `len`, `snapshot`) only perform an atomic load of the
+/// current version of the set, so they never block and never contend with
+/// each other. Writers copy the current set, modify the copy, and atomically
+/// publish it, retrying if another writer got there first. This makes writes
+/// much more expensive than with a `RwLock`, which is the right trade-off for
+/// small allow-lists and similar sets that are read far more often than they
+/// are changed.
+///
+/// ```
+/// use smallset::{SharedSmallSet, SmallSet};
+///
+/// let allowed: SharedSmallSet<[u32; 4]> = SharedSmallSet::new();
+/// allowed.insert(80);
+/// allowed.insert(443);
+/// assert!(allowed.contains(&443));
+/// assert!(!allowed.contains(&22));
+/// ```
+pub struct SharedSmallSet<A: Array> {
+    current: ArcSwap<SmallSet<A>>,
+}
+
+impl<A: Array> SharedSmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    /// Creates a new, empty `SharedSmallSet`.
+    pub fn new() -> SharedSmallSet<A> {
+        SharedSmallSet::from_set(SmallSet::new())
+    }
+
+    /// Creates a `SharedSmallSet` whose initial contents are `set`.
+    pub fn from_set(set: SmallSet<A>) -> SharedSmallSet<A> {
+        SharedSmallSet {
+            current: ArcSwap::from_pointee(set),
+        }
+    }
+
+    /// Tests whether `elem` is present in the current version of the set.
+    #[inline]
+    pub fn contains(&self, elem: &A::Item) -> bool {
+        self.current.load().contains(elem)
+    }
+
+    /// Returns the length of the current version of the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.current.load().len()
+    }
+
+    /// Returns `true` if the current version of the set is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.current.load().is_empty()
+    }
+
+    /// Returns the current version of the set. The snapshot is not affected by
+    /// later updates, so it can be used to run several queries against a
+    /// consistent view.
+    pub fn snapshot(&self) -> Arc<SmallSet<A>> {
+        self.current.load_full()
+    }
+
+    /// Replaces the contents of the set with `set`.
+    pub fn store(&self, set: SmallSet<A>) {
+        self.current.store(Arc::new(set));
+    }
+}
+
+impl<A: Array> SharedSmallSet<A>
+where
+    A::Item: PartialEq + Eq + Clone,
+{
+    /// Inserts `elem` if not yet present. Returns `true` if this call inserted
+    /// it.
+    pub fn insert(&self, elem: A::Item) -> bool {
+        let mut inserted = false;
+        self.update(|set| inserted = set.insert(elem.clone()));
+        inserted
+    }
+
+    /// Removes `elem`. Returns `true` if this call removed it.
+    pub fn remove(&self, elem: &A::Item) -> bool {
+        let mut removed = false;
+        self.update(|set| removed = set.remove(elem));
+        removed
+    }
+
+    /// Applies `f` to a copy of the current set and publishes the result.
+    /// If another writer publishes first, `f` is run again on a fresh copy,
+    /// so it may be called more than once and should not have other side
+    /// effects.
+    pub fn update<F>(&self, mut f: F)
+    where
+        F: FnMut(&mut SmallSet<A>),
+    {
+        self.current.rcu(|cur| {
+            let mut next = SmallSet::clone(cur);
+            f(&mut next);
+            next
+        });
+    }
+}
+
+impl<A: Array> Default for SharedSmallSet<A>
+where
+    A::Item: PartialEq + Eq,
+{
+    fn default() -> SharedSmallSet<A> {
+        SharedSmallSet::new()
+    }
+}
+
+impl<A: Array> fmt::Debug for SharedSmallSet<A>
+where
+    A::Item: PartialEq + Eq + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.current.load().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_basic() {
+        let s: SharedSmallSet<[u32; 2]> = SharedSmallSet::new();
+        assert!(s.insert(1));
+        assert!(!s.insert(1));
+        let snap = s.snapshot();
+        assert!(s.insert(2));
+        assert!(s.remove(&1));
+        assert!(!s.remove(&1));
+        assert!(snap.contains(&1) && !snap.contains(&2));
+        assert!(!s.contains(&1) && s.contains(&2));
+        assert!(s.len() == 1);
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        let s: Arc<SharedSmallSet<[u32; 4]>> = Arc::new(SharedSmallSet::new());
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let s = s.clone();
+                thread::spawn(move || {
+                    for i in 0..50 {
+                        s.insert(t * 100 + i);
+                        assert!(s.contains(&(t * 100 + i)));
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert!(s.len() == 200);
+    }
+}
@@ -0,0 +1,84 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! Element search, vectorized for primitive integers when the `simd` feature
+//! is enabled.
+//!
+//! Without `simd`, every element type is compared one element at a time.
+//! With `simd`, which requires a nightly compiler, `u8`, `u16`, `u32` and
+//! `u64` slices are compared a vector of lanes at a time using `std::simd`,
+//! with a scalar loop for the remainder.
+
+pub(crate) trait SpecFind: PartialEq + Sized {
+    fn position(slice: &[Self], elem: &Self) -> Option<usize>;
+}
+
+#[cfg(not(feature = "simd"))]
+impl<T: PartialEq> SpecFind for T {
+    #[inline]
+    fn position(slice: &[T], elem: &T) -> Option<usize> {
+        slice.iter().position(|e| *e == *elem)
+    }
+}
+
+#[cfg(feature = "simd")]
+impl<T: PartialEq> SpecFind for T {
+    #[inline]
+    default fn position(slice: &[T], elem: &T) -> Option<usize> {
+        slice.iter().position(|e| *e == *elem)
+    }
+}
+
+#[cfg(feature = "simd")]
+macro_rules! simd_find {
+    ($($t:ty => $lanes:expr),*) => {
+        $(
+            impl SpecFind for $t {
+                #[inline]
+                fn position(slice: &[$t], elem: &$t) -> Option<usize> {
+                    use std::simd::cmp::SimdPartialEq;
+                    use std::simd::Simd;
+
+                    let needle = Simd::<$t, $lanes>::splat(*elem);
+                    let chunks = slice.chunks_exact($lanes);
+                    let rest = chunks.remainder();
+                    for (i, chunk) in chunks.enumerate() {
+                        let eq = Simd::<$t, $lanes>::from_slice(chunk).simd_eq(needle);
+                        if let Some(lane) = eq.first_set() {
+                            return Some(i * $lanes + lane);
+                        }
+                    }
+                    let base = slice.len() - rest.len();
+                    rest.iter().position(|e| *e == *elem).map(|j| base + j)
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "simd")]
+simd_find!(u8 => 16, u16 => 16, u32 => 8, u64 => 4);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_position() {
+        let v: Vec<u32> = (0..37).collect();
+        for i in 0..37 {
+            assert!(SpecFind::position(&v[..], &i) == Some(i as usize));
+        }
+        assert!(SpecFind::position(&v[..], &37).is_none());
+        assert!(SpecFind::position(&[] as &[u8], &0).is_none());
+        let w: [u8; 4] = [9, 9, 3, 9];
+        assert!(SpecFind::position(&w[..], &9) == Some(0));
+        let s = ["a".to_string(), "b".to_string()];
+        assert!(SpecFind::position(&s[..], &"b".to_string()) == Some(1));
+    }
+}
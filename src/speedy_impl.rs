@@ -0,0 +1,76 @@
+// smallset: a Rust crate for small unordered sets of elements, built on top of
+// `smallvec`.
+//
+// Copyright (c) 2016 Chris Fallin <cfallin@c1f.net>. Released under the MIT license.
+//
+
+//! `speedy` support, enabled with the `speedy` feature.
+//!
+//! A set is written exactly like a `Vec` of its elements (a `u32` length
+//! followed by the elements in iteration order), so the two are
+//! interchangeable in a stream. Duplicate elements are dropped on read.
+
+use smallvec::Array;
+use speedy::{Context, Readable, Reader, Writable, Writer};
+
+use SmallSet;
+
+impl<'a, C, A> Readable<'a, C> for SmallSet<A>
+where
+    C: Context,
+    A: Array,
+    A::Item: PartialEq + Eq + Readable<'a, C>,
+{
+    fn read_from<R: Reader<'a, C>>(reader: &mut R) -> Result<Self, C::Error> {
+        let len = reader.read_u32()? as usize;
+        reader.read_collection(len)
+    }
+
+    fn minimum_bytes_needed() -> usize {
+        4
+    }
+}
+
+impl<C, A> Writable<C> for SmallSet<A>
+where
+    C: Context,
+    A: Array,
+    A::Item: PartialEq + Eq + Writable<C>,
+{
+    fn write_to<W: ?Sized + Writer<C>>(&self, writer: &mut W) -> Result<(), C::Error> {
+        self.elements.as_slice().write_to(writer)
+    }
+
+    fn bytes_needed(&self) -> Result<usize, C::Error> {
+        Writable::<C>::bytes_needed(self.elements.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use speedy::LittleEndian;
+
+    #[test]
+    fn test_roundtrip() {
+        let s: SmallSet<[u16; 2]> = vec![3, 1, 2].into_iter().collect();
+        let bytes = s.write_to_vec_with_ctx(LittleEndian::default()).unwrap();
+        assert!(bytes == vec![3, 0, 0, 0, 3, 0, 1, 0, 2, 0]);
+        let d: SmallSet<[u16; 2]> =
+            SmallSet::read_from_buffer_with_ctx(LittleEndian::default(), &bytes).unwrap();
+        assert!(d.iter().copied().collect::<Vec<u16>>() == vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_compatible_with_vec() {
+        let v: Vec<String> = vec!["a".into(), "b".into(), "a".into()];
+        let bytes = v.write_to_vec_with_ctx(LittleEndian::default()).unwrap();
+        let d: SmallSet<[String; 2]> =
+            SmallSet::read_from_buffer_with_ctx(LittleEndian::default(), &bytes).unwrap();
+        assert!(d.len() == 2);
+        let bytes2 = d.write_to_vec_with_ctx(LittleEndian::default()).unwrap();
+        let v2: Vec<String> =
+            Vec::read_from_buffer_with_ctx(LittleEndian::default(), &bytes2).unwrap();
+        assert!(v2 == vec!["a".to_string(), "b".to_string()]);
+    }
+}